@@ -1,6 +1,65 @@
+// Layout descriptions are validated on the host so broken presets never reach the device
+#[path = "src/layout/mod.rs"]
+#[allow(dead_code)]
+mod layout;
+
+#[path = "src/icons/codegen.rs"]
+mod codegen;
+
+use std::env;
+use std::fs;
+use std::path::Path;
+use embedded_graphics::{geometry::*, primitives::Rectangle};
+
+// Must match `DISPLAY_WIDTH` and `DISPLAY_HEIGHT` in src/display/display.rs
+const PANEL_SIZE: Size = Size::new(800, 480);
+
 // Necessary because of this issue: https://github.com/rust-lang/cargo/issues/9641
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    validate_layouts()?;
+    generate_icon_registry()?;
+
     embuild::build::CfgArgs::output_propagated("ESP_IDF")?;
     embuild::build::LinkArgs::output_propagated("ESP_IDF")?;
     Ok(())
 }
+
+/// Checks the built-in presets and any custom layout in `layouts/` fit the panel
+fn validate_layouts() -> Result<(), Box<dyn std::error::Error>> {
+    let panel = Rectangle::new(Point::zero(), PANEL_SIZE);
+
+    for dir in ["src/layout/presets", "layouts"] {
+        println!("cargo:rerun-if-changed={}", dir);
+        let dir = Path::new(dir);
+        if !dir.exists() {
+            continue;
+        }
+
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().map_or(true, |ext| ext != "json") {
+                continue;
+            }
+            println!("cargo:rerun-if-changed={}", path.display());
+
+            let json = fs::read_to_string(&path)?;
+            layout::description::parse(&json)
+                .and_then(|node| node.compute(panel))
+                .and_then(|layout| layout.validate())
+                .map_err(|err| format!("Invalid layout {}: {:#}", path.display(), err))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Generates `icons.rs` in `OUT_DIR`, see src/icons/codegen.rs.
+/// Which icons end up in flash is decided by the tables of src/icons/glyphs.rs.
+fn generate_icon_registry() -> Result<(), Box<dyn std::error::Error>> {
+    let root = Path::new("src/icons/qoi");
+    println!("cargo:rerun-if-changed={}", root.display());
+
+    let code = codegen::icon_registry(root)?;
+    fs::write(Path::new(&env::var("OUT_DIR")?).join("icons.rs"), code)?;
+    Ok(())
+}
@@ -0,0 +1,4 @@
+//! Sun and moon calculations, independent of the display.
+
+pub mod moon;
+pub mod sun;
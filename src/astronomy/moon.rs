@@ -0,0 +1,113 @@
+use std::f32::consts::TAU;
+
+/// Average length of a lunation in days
+pub const SYNODIC_MONTH: f32 = 29.530588;
+
+/// Number of distinct phase icons, from new moon through a whole lunation
+pub const PHASE_STEPS: usize = 28;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PhaseName {
+    NewMoon,
+    WaxingCrescent,
+    FirstQuarter,
+    WaxingGibbous,
+    FullMoon,
+    WaningGibbous,
+    ThirdQuarter,
+    WaningCrescent,
+}
+
+impl PhaseName {
+    pub fn label(&self) -> &'static str {
+        match self {
+            PhaseName::NewMoon => "New moon",
+            PhaseName::WaxingCrescent => "Waxing crescent",
+            PhaseName::FirstQuarter => "First quarter",
+            PhaseName::WaxingGibbous => "Waxing gibbous",
+            PhaseName::FullMoon => "Full moon",
+            PhaseName::WaningGibbous => "Waning gibbous",
+            PhaseName::ThirdQuarter => "Third quarter",
+            PhaseName::WaningCrescent => "Waning crescent",
+        }
+    }
+}
+
+/// Moon phase as reported by OpenWeather: 0 and 1 are new moon, 0.25 first
+/// quarter, 0.5 full moon and 0.75 third quarter.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MoonPhase {
+    phase: f32,
+}
+
+impl MoonPhase {
+    pub fn new(phase: f32) -> Self {
+        MoonPhase {
+            phase: phase.rem_euclid(1.0),
+        }
+    }
+
+    /// Index of the closest phase icon, 0 being new moon and 14 full moon
+    pub fn step(&self) -> usize {
+        (self.phase * PHASE_STEPS as f32).round() as usize % PHASE_STEPS
+    }
+
+    pub fn name(&self) -> PhaseName {
+        match self.step() {
+            0 => PhaseName::NewMoon,
+            1..=6 => PhaseName::WaxingCrescent,
+            7 => PhaseName::FirstQuarter,
+            8..=13 => PhaseName::WaxingGibbous,
+            14 => PhaseName::FullMoon,
+            15..=20 => PhaseName::WaningGibbous,
+            21 => PhaseName::ThirdQuarter,
+            _ => PhaseName::WaningCrescent,
+        }
+    }
+
+    /// Illuminated fraction of the disc, 0 to 1
+    pub fn illumination(&self) -> f32 {
+        (1.0 - (self.phase * TAU).cos()) / 2.0
+    }
+
+    pub fn days_to_full(&self) -> f32 {
+        (0.5 - self.phase).rem_euclid(1.0) * SYNODIC_MONTH
+    }
+
+    pub fn days_to_new(&self) -> f32 {
+        (1.0 - self.phase).rem_euclid(1.0) * SYNODIC_MONTH
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn phases_are_named() {
+        for (phase, step, name) in [
+            (0.0, 0, PhaseName::NewMoon),
+            (0.1, 3, PhaseName::WaxingCrescent),
+            (0.25, 7, PhaseName::FirstQuarter),
+            (0.4, 11, PhaseName::WaxingGibbous),
+            (0.5, 14, PhaseName::FullMoon),
+            (0.6, 17, PhaseName::WaningGibbous),
+            (0.75, 21, PhaseName::ThirdQuarter),
+            (0.85, 24, PhaseName::WaningCrescent),
+            (0.99, 0, PhaseName::NewMoon),
+            (-0.25, 21, PhaseName::ThirdQuarter),
+        ] {
+            let moon = MoonPhase::new(phase);
+            assert_eq!((moon.step(), moon.name()), (step, name), "phase {}", phase);
+        }
+    }
+
+    #[test]
+    fn days_to_full_and_new_moon() {
+        let first_quarter = MoonPhase::new(0.25);
+        assert!((first_quarter.days_to_full() - SYNODIC_MONTH / 4.0).abs() < 1e-3);
+        assert!((first_quarter.days_to_new() - SYNODIC_MONTH * 3.0 / 4.0).abs() < 1e-3);
+        assert!((first_quarter.illumination() - 0.5).abs() < 1e-3);
+        assert_eq!(MoonPhase::new(0.0).days_to_new(), 0.0);
+    }
+}
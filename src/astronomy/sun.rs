@@ -0,0 +1,117 @@
+use std::f32::consts::TAU;
+use time::Date;
+
+/// Sun altitude at the start of civil dawn and the end of civil dusk, in degrees
+pub const CIVIL_TWILIGHT: f32 = -6.0;
+
+/// Unix timestamps of the moments the sun crosses `altitude` on `date`,
+/// rising then setting. `None` when it stays above or below all day.
+///
+/// Uses the NOAA approximations of the solar declination and equation of
+/// time, accurate to a couple of minutes outside of polar regions.
+pub fn crossing_times(date: Date, latitude: f32, longitude: f32, altitude: f32) -> Option<(i64, i64)> {
+    // Fraction of the year at noon
+    let gamma = TAU / 365.0 * (date.ordinal() as f32 - 0.5);
+    let equation_of_time = 229.18 * (0.000075 + 0.001868 * gamma.cos() - 0.032077 * gamma.sin()
+        - 0.014615 * (2.0 * gamma).cos() - 0.040849 * (2.0 * gamma).sin());
+    let declination = 0.006918 - 0.399912 * gamma.cos() + 0.070257 * gamma.sin()
+        - 0.006758 * (2.0 * gamma).cos() + 0.000907 * (2.0 * gamma).sin()
+        - 0.002697 * (3.0 * gamma).cos() + 0.00148 * (3.0 * gamma).sin();
+
+    let latitude = latitude.to_radians();
+    let cos_hour_angle = (altitude.to_radians().sin() - latitude.sin() * declination.sin())
+        / (latitude.cos() * declination.cos());
+    if !(-1.0..=1.0).contains(&cos_hour_angle) {
+        return None;
+    }
+    // Degrees of rotation are 4 minutes each
+    let half_day = cos_hour_angle.acos().to_degrees() * 4.0;
+    let solar_noon = 720.0 - 4.0 * longitude - equation_of_time;
+
+    let midnight = date.midnight().assume_utc().unix_timestamp();
+    let at = |minutes: f32| midnight + (minutes * 60.0).round() as i64;
+    Some((at(solar_noon - half_day), at(solar_noon + half_day)))
+}
+
+/// Start of civil dawn and end of civil dusk on `date`
+pub fn civil_twilight(date: Date, latitude: f32, longitude: f32) -> Option<(i64, i64)> {
+    crossing_times(date, latitude, longitude, CIVIL_TWILIGHT)
+}
+
+/// Progress of the sun between `sunrise` and `sunset`, 0 to 1, `None` at night
+pub fn day_progress(now: u64, sunrise: u64, sunset: u64) -> Option<f32> {
+    if sunset <= sunrise || now < sunrise || now > sunset {
+        return None;
+    }
+    Some((now - sunrise) as f32 / (sunset - sunrise) as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::{Month, Time};
+
+    /// Sun altitude at sunrise and sunset, refraction and the disc's radius included
+    const HORIZON: f32 = -0.833;
+
+    fn date(year: i32, month: Month, day: u8) -> Date {
+        Date::from_calendar_date(year, month, day).unwrap()
+    }
+
+    fn utc(date: Date, hour: u8, minute: u8) -> i64 {
+        date.with_time(Time::from_hms(hour, minute, 0).unwrap()).assume_utc().unix_timestamp()
+    }
+
+    fn assert_near(actual: (i64, i64), expected: (i64, i64)) {
+        // The approximations are good to a couple of minutes
+        assert!((actual.0 - expected.0).abs() <= 180, "rise {} expected {}", actual.0, expected.0);
+        assert!((actual.1 - expected.1).abs() <= 180, "set {} expected {}", actual.1, expected.1);
+    }
+
+    #[test]
+    fn mid_latitude_times_match_noaa() {
+        // London on the summer solstice, from the NOAA solar calculator
+        let solstice = date(2024, Month::June, 21);
+        assert_near(crossing_times(solstice, 51.507, -0.128, HORIZON).unwrap(), (utc(solstice, 3, 43), utc(solstice, 20, 22)));
+        assert_near(civil_twilight(solstice, 51.507, -0.128).unwrap(), (utc(solstice, 2, 55), utc(solstice, 21, 9)));
+
+        // New York on the equinox, west of Greenwich
+        let equinox = date(2024, Month::March, 20);
+        assert_near(crossing_times(equinox, 40.713, -74.006, HORIZON).unwrap(), (utc(equinox, 10, 58), utc(equinox, 23, 9)));
+        assert_near(civil_twilight(equinox, 40.713, -74.006).unwrap(), (utc(equinox, 10, 31), utc(equinox, 23, 36)));
+    }
+
+    #[test]
+    fn polar_day_and_night_have_no_crossings() {
+        let summer = date(2024, Month::June, 21);
+        let winter = date(2024, Month::December, 21);
+
+        // Tromsø: midnight sun, then polar night with a civil twilight at noon
+        assert_eq!(crossing_times(summer, 69.65, 18.96, HORIZON), None);
+        assert_eq!(civil_twilight(summer, 69.65, 18.96), None);
+        assert_eq!(crossing_times(winter, 69.65, 18.96, HORIZON), None);
+        assert!(civil_twilight(winter, 69.65, 18.96).is_some());
+
+        // Svalbard stays dark through civil twilight too
+        assert_eq!(civil_twilight(winter, 78.22, 15.65), None);
+        // Seasons are the other way round in the south
+        assert_eq!(crossing_times(summer, -77.85, 166.67, HORIZON), None);
+        assert_eq!(crossing_times(winter, -77.85, 166.67, HORIZON), None);
+    }
+
+    #[test]
+    fn progress_runs_from_sunrise_to_sunset() {
+        assert_eq!(day_progress(1000, 1000, 3000), Some(0.0));
+        assert_eq!(day_progress(2000, 1000, 3000), Some(0.5));
+        assert_eq!(day_progress(3000, 1000, 3000), Some(1.0));
+    }
+
+    #[test]
+    fn no_progress_at_night_or_for_inverted_ranges() {
+        assert_eq!(day_progress(999, 1000, 3000), None);
+        assert_eq!(day_progress(3001, 1000, 3000), None);
+        // Sunset before sunrise, e.g. times of different days
+        assert_eq!(day_progress(2000, 3000, 1000), None);
+        assert_eq!(day_progress(1000, 1000, 1000), None);
+    }
+}
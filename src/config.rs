@@ -7,12 +7,66 @@ pub struct Config {
     wifi_psk: &'static str,
     #[default("")]
     owm_api_key: &'static str,
-    #[default("Montreal, Quebec")]
+    /// Display name, looked up from the coordinates when empty
+    #[default("")]
     location_name: &'static str,
     #[default(45.5019)]
     latitude: f32,
     #[default(-73.5674)]
     longitude: f32,
+    /// City ("Montreal,QC,CA") or postal code ("H2X,CA") to look up the
+    /// coordinates and name from, replaces the three settings above
+    #[default("")]
+    location_query: &'static str,
     #[default(8)]
     hours_to_draw: usize,
+    /// Preset layout, see `layout::description::PRESETS`
+    #[default("overview")]
+    layout: &'static str,
+    /// JSON layout description downloaded at boot and kept in NVS, see
+    /// `layout::description`. Replaces the preset above once it proved valid.
+    #[default("")]
+    layout_url: &'static str,
+    /// Comma separated pages to rotate through, see `pages::Page`
+    #[default("overview,hourly,daily,astronomy")]
+    pages: &'static str,
+    /// Seconds each page stays on screen
+    #[default(60)]
+    page_duration: u64,
+    /// Seconds between weather report updates
+    #[default(60)]
+    fetch_interval: u64,
+    /// Locations to cycle through, see `locations::parse_locations`.
+    /// The single location above is used when empty.
+    #[default("")]
+    locations: &'static str,
+    /// OpenWeather calls allowed per day, for all locations together.
+    /// Fetch intervals asking for more are stretched to fit.
+    #[default(1000)]
+    api_daily_budget: u32,
+    /// Icon theme, see `icons::theme::Theme`. The `next_theme` button action
+    /// switches themes at runtime, its choice is kept in NVS and wins over this one.
+    #[default("weather-icons")]
+    icon_theme: &'static str,
+    /// Cloud cover in % from which icons show a clouded sky
+    #[default(60)]
+    cloudy_threshold: u8,
+    /// Wind speed and gusts in m/s from which icons show wind
+    #[default(32.2)]
+    windy_speed: f32,
+    #[default(40.2)]
+    windy_gust: f32,
+    /// Button actions: next_page, force_fetch, diagnostics, next_theme or none
+    #[default("next_page")]
+    button_short: &'static str,
+    #[default("force_fetch")]
+    button_double: &'static str,
+    #[default("diagnostics")]
+    button_long: &'static str,
+    /// Firmware manifest to check for updates, see `ota::manifest`. Disabled when empty.
+    #[default("")]
+    ota_manifest_url: &'static str,
+    /// Seconds between two checks for a firmware update
+    #[default(86400)]
+    ota_check_interval: u64,
 }
\ No newline at end of file
@@ -0,0 +1,113 @@
+use embedded_graphics::{
+    geometry::*,
+    primitives::Rectangle,
+};
+
+/// Bounding box of the pixels that differ between two 1 bit frame buffers
+/// `width` pixels wide, rows packed MSB first. `None` when they are identical.
+pub fn diff_bounds(old: &[u8], new: &[u8], width: u32) -> Option<Rectangle> {
+    let bytes_per_row = (width / 8) as usize;
+    let mut top_left = Point::new(i32::MAX, i32::MAX);
+    let mut bottom_right = Point::new(i32::MIN, i32::MIN);
+
+    for (index, (a, b)) in old.iter().zip(new.iter()).enumerate() {
+        let changed = a ^ b;
+        if changed == 0 {
+            continue;
+        }
+        // MSB is the leftmost pixel of the byte
+        let x = ((index % bytes_per_row) * 8) as i32;
+        let y = (index / bytes_per_row) as i32;
+        let first = x + changed.leading_zeros() as i32;
+        let last = x + 7 - changed.trailing_zeros() as i32;
+
+        top_left = Point::new(top_left.x.min(first), top_left.y.min(y));
+        bottom_right = Point::new(bottom_right.x.max(last), bottom_right.y.max(y));
+    }
+
+    if top_left.x == i32::MAX {
+        None
+    } else {
+        Some(Rectangle::with_corners(top_left, bottom_right))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WIDTH: u32 = 800;
+    const HEIGHT: u32 = 480;
+    const LEN: usize = (WIDTH * HEIGHT / 8) as usize;
+
+    fn frame() -> Vec<u8> {
+        vec![0; LEN]
+    }
+
+    fn set(frame: &mut [u8], x: u32, y: u32) {
+        let index = (y * WIDTH + x) as usize;
+        frame[index / 8] |= 0x80 >> (index % 8);
+    }
+
+    fn rect(x: i32, y: i32, width: u32, height: u32) -> Option<Rectangle> {
+        Some(Rectangle::new(Point::new(x, y), Size::new(width, height)))
+    }
+
+    #[test]
+    fn identical_frames_have_no_diff() {
+        assert_eq!(diff_bounds(&frame(), &frame(), WIDTH), None);
+        let mut drawn = frame();
+        set(&mut drawn, 10, 10);
+        assert_eq!(diff_bounds(&drawn, &drawn, WIDTH), None);
+        assert_eq!(diff_bounds(&[], &[], WIDTH), None);
+    }
+
+    #[test]
+    fn single_pixels_are_found() {
+        for (x, y) in [(0, 0), (3, 0), (13, 7), (WIDTH - 1, HEIGHT - 1), (0, HEIGHT - 1)] {
+            let mut new = frame();
+            set(&mut new, x, y);
+            assert_eq!(diff_bounds(&frame(), &new, WIDTH), rect(x as i32, y as i32, 1, 1));
+            // Erasing counts the same as drawing
+            assert_eq!(diff_bounds(&new, &frame(), WIDTH), rect(x as i32, y as i32, 1, 1));
+        }
+    }
+
+    #[test]
+    fn x_bounds_are_exact_at_byte_edges() {
+        // Last pixel of one byte to the first of the next
+        let mut new = frame();
+        set(&mut new, 7, 5);
+        set(&mut new, 8, 6);
+        assert_eq!(diff_bounds(&frame(), &new, WIDTH), rect(7, 5, 2, 2));
+
+        // A whole byte
+        let mut new = frame();
+        new[2] = 0xFF;
+        assert_eq!(diff_bounds(&frame(), &new, WIDTH), rect(16, 0, 8, 1));
+
+        // Bounds inside a byte don't widen to it
+        let mut new = frame();
+        new[(WIDTH / 8) as usize + 1] = 0b0011_0100;
+        assert_eq!(diff_bounds(&frame(), &new, WIDTH), rect(10, 1, 4, 1));
+    }
+
+    #[test]
+    fn changes_spanning_rows_are_combined() {
+        let mut new = frame();
+        set(&mut new, 100, 20);
+        set(&mut new, 40, 60);
+        set(&mut new, 70, 30);
+        assert_eq!(diff_bounds(&frame(), &new, WIDTH), rect(40, 20, 61, 41));
+    }
+
+    #[test]
+    fn full_frame_diff_covers_the_display() {
+        assert_eq!(diff_bounds(&frame(), &vec![0xFF; LEN], WIDTH), rect(0, 0, WIDTH, HEIGHT));
+
+        let mut corners = frame();
+        set(&mut corners, 0, 0);
+        set(&mut corners, WIDTH - 1, HEIGHT - 1);
+        assert_eq!(diff_bounds(&frame(), &corners, WIDTH), rect(0, 0, WIDTH, HEIGHT));
+    }
+}
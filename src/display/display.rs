@@ -6,8 +6,11 @@ use embedded_graphics::{
     prelude::*,
     primitives::{Rectangle},
 };
-use esp_idf_sys::EspError;
-use crate::display::display_driver::{DisplayDriver};
+use log::*;
+use crate::display::diff::diff_bounds;
+use crate::display::display_driver::{DisplayDriver, PowerState};
+use crate::display::error::DisplayError;
+use crate::display::status::PanelStatus;
 
 pub const DISPLAY_HEIGHT: u32 = 480;
 pub const DISPLAY_WIDTH: u32 = 800;
@@ -23,33 +26,101 @@ pub struct DisplayConfig {
 pub struct Display {
     driver: DisplayDriver,
     pixels: Vec<u8>,
+    /// Last frame transmitted to the panel, `None` when the panel content is unknown
+    last_frame: Option<Vec<u8>>,
     config: DisplayConfig,
 }
 
 impl Display {
-    pub fn new(mut driver: DisplayDriver, config: DisplayConfig) -> Result<Display, EspError> {
+    pub fn new(mut driver: DisplayDriver, config: DisplayConfig) -> Result<Display, DisplayError> {
         driver.init()?;
+        match driver.revision() {
+            Ok(revision) => info!("Panel revision: {:?}", revision),
+            Err(DisplayError::ReadUnsupported) => {}
+            Err(err) => warn!("Failed to read panel revision: {}", err),
+        }
 
         Ok(Display {
             driver,
             pixels: vec![BinaryColor::Off.into_storage() ; BUFFER_SIZE],
+            last_frame: None,
             config,
         })
     }
-    pub fn flush(&mut self) -> Result<(), EspError> {
-        self.driver.transmit_frame(&self.pixels)
+    pub fn flush(&mut self) -> Result<(), DisplayError> {
+        self.driver.transmit_frame(&self.pixels)?;
+
+        match &mut self.last_frame {
+            Some(last_frame) => last_frame.copy_from_slice(&self.pixels),
+            None => self.last_frame = Some(self.pixels.clone()),
+        }
+        Ok(())
     }
-    pub fn refresh(&mut self) -> Result<(), EspError> {
-        self.driver.refresh()
+    pub fn refresh(&mut self) -> Result<(), DisplayError> {
+        if self.driver.power_state() == PowerState::DeepSleep {
+            // Waking up resets the controller SRAM, send the frame again
+            warn!("Refresh requested while the display is asleep");
+            self.flush()?;
+        }
+        self.driver.refresh().map_err(|err| {
+            // Can't tell what the panel ended up showing
+            self.invalidate();
+            err
+        })
     }
-    pub fn flush_and_refresh(&mut self) -> Result<(), EspError> {
+    /// Transmits and refreshes the frame buffer, unless it is identical to the last frame sent.
+    pub fn flush_and_refresh(&mut self) -> Result<(), DisplayError> {
+        match self.changed_region() {
+            None => {
+                info!("Frame unchanged, skipping refresh");
+                return Ok(());
+            }
+            Some(region) => info!("Frame changed in {:?}", region),
+        }
+
         self.flush()?;
         self.refresh()
     }
-    pub fn clear_screen(&mut self, clear_frame_buffer: bool) -> Result<(), EspError> {
+    /// Puts the panel in deep sleep until the next flush or refresh.
+    pub fn sleep(&mut self) -> Result<(), DisplayError> {
+        self.driver.deep_sleep()
+    }
+    /// Bounding box of the pixels that differ from the last transmitted frame.
+    ///
+    /// Returns `None` when nothing changed and the whole display area when the
+    /// panel content is unknown.
+    pub fn changed_region(&self) -> Option<Rectangle> {
+        match &self.last_frame {
+            Some(last_frame) => diff_bounds(last_frame, &self.pixels, DISPLAY_WIDTH),
+            None => Some(DISPLAY_AREA),
+        }
+    }
+    /// Forgets the last transmitted frame so the next `flush_and_refresh` always refreshes.
+    pub fn invalidate(&mut self) {
+        self.last_frame = None;
+    }
+    /// Hard resets and re-initializes the panel after a failure.
+    /// The frame buffer is kept so the next flush redraws the last frame.
+    pub fn recover(&mut self) -> Result<(), DisplayError> {
+        warn!("Recovering display");
+        self.invalidate();
+        // init starts with a hardware reset
+        self.driver.init()
+    }
+    pub fn status(&mut self) -> Result<PanelStatus, DisplayError> {
+        self.driver.status()
+    }
+    pub fn temperature(&mut self) -> Result<f32, DisplayError> {
+        self.driver.temperature()
+    }
+    pub fn is_low_power(&mut self) -> Result<bool, DisplayError> {
+        self.driver.is_low_power()
+    }
+    pub fn clear_screen(&mut self, clear_frame_buffer: bool) -> Result<(), DisplayError> {
         if clear_frame_buffer {
             self.pixels = vec![BinaryColor::Off.into_storage() ; BUFFER_SIZE];
         }
+        self.invalidate();
         self.driver.clear_screen()
     }
 
@@ -135,4 +206,4 @@ impl DrawTarget for Display {
         Ok(())
     }
 
-}
\ No newline at end of file
+}
@@ -1,16 +1,36 @@
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use anyhow::Result;
 use esp_idf_hal::{gpio::*, delay::*, spi::*, delay};
-use esp_idf_sys::EspError;
 use log::*;
 use crate::display::command::Command;
 use crate::display::display::{BUFFER_SIZE};
+use crate::display::error::DisplayError;
+use crate::display::status::{PanelStatus, Revision, temperature_from_raw};
 use crate::display::traits;
 
 pub struct DisplayDriver {
     spi: SpiDeviceDriver<'static, SpiDriver<'static>>,
     pins: DisplayPins,
     config: DisplayDriverConfig,
+    state: PowerState,
+}
+
+/// Power state of the panel controller.
+///
+/// High voltage should only be applied while the panel is refreshing, so the
+/// driver powers on before transmitting, powers off once the refresh completes
+/// and can be put in deep sleep in between updates.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PowerState {
+    /// Never initialized, or state lost after a failure
+    Uninitialized,
+    /// Charge pump and drivers are running
+    On,
+    /// Registers are kept, high voltage is off
+    Off,
+    /// Only a hardware reset followed by init wakes the controller up.
+    /// SRAM content is lost.
+    DeepSleep,
 }
 
 pub struct DisplayPins {
@@ -20,8 +40,27 @@ pub struct DisplayPins {
     pub rst: PinDriver<'static, AnyOutputPin, Output>,
 }
 
+/// How the panel data line is wired, which decides whether registers can be read back.
+#[allow(dead_code)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ReadMode {
+    /// Only SDO is connected, the panel can't be read.
+    WriteOnly,
+    /// The panel SDA line is bidirectional and shared with SDO.
+    /// The SPI bus must be configured with `Duplex::Half3Wire`.
+    ThreeWire,
+    /// The panel output is wired to a separate SDI pin.
+    FourWire,
+}
+
 pub struct DisplayDriverConfig {
+    /// Polling interval while waiting for the panel
     pub delay: Duration,
+    /// Maximum time to wait for the panel before giving up on short operations
+    pub busy_timeout: Duration,
+    /// Maximum time to wait for a full refresh to complete
+    pub refresh_timeout: Duration,
+    pub read_mode: ReadMode,
 }
 
 impl DisplayDriver
@@ -31,25 +70,92 @@ impl DisplayDriver
             spi: driver,
             pins,
             config,
+            state: PowerState::Uninitialized,
         }
     }
 
-    pub fn transmit_frame(&mut self, frame: &[u8]) -> Result<(), EspError> {
-        self.wait_until_idle()?;
+    pub fn power_state(&self) -> PowerState {
+        self.state
+    }
+
+    pub fn transmit_frame(&mut self, frame: &[u8]) -> Result<(), DisplayError> {
+        self.power_on()?;
+        self.wait_until_idle(self.config.busy_timeout)?;
         info!("Transmitting frame");
         self.cmd_with_data(Command::DataStartTransmission2, frame)
     }
 
-    pub fn refresh(&mut self) -> Result<(), EspError> {
+    /// Refreshes the panel from SRAM and powers it off once done.
+    pub fn refresh(&mut self) -> Result<(), DisplayError> {
+        self.power_on()?;
+
+        if self.config.read_mode != ReadMode::WriteOnly {
+            // Informational only, a failed read shouldn't prevent the refresh
+            match self.temperature() {
+                Ok(temperature) if !(0.0..=50.0).contains(&temperature) => {
+                    warn!("Panel temperature {}°C outside of operating range, refresh may be slow or incomplete", temperature);
+                }
+                Ok(temperature) => info!("Panel temperature: {}°C", temperature),
+                Err(err) => warn!("Failed to read panel temperature: {}", err),
+            }
+        }
+
         info!("Display refresh");
         self.cmd(Command::DisplayRefresh)?;
         Delay::delay_ms(50);
 
-        self.wait_until_idle()
+        self.wait_until_idle(self.config.refresh_timeout)?;
+
+        self.power_off()
+    }
+
+    /// Brings the panel to `PowerState::On`, re-initializing it when coming out of deep sleep.
+    pub fn power_on(&mut self) -> Result<(), DisplayError> {
+        match self.state {
+            PowerState::On => Ok(()),
+            PowerState::Off => {
+                info!("Display power on");
+                self.cmd(Command::PowerOn)?;
+                Delay::delay_ms(100);
+                self.wait_until_idle(self.config.busy_timeout)?;
+                self.state = PowerState::On;
+                Ok(())
+            }
+            PowerState::Uninitialized | PowerState::DeepSleep => self.init(),
+        }
+    }
+
+    pub fn power_off(&mut self) -> Result<(), DisplayError> {
+        if self.state != PowerState::On {
+            return Ok(());
+        }
+
+        info!("Display power off");
+        self.cmd(Command::PowerOff)?;
+        self.wait_until_idle(self.config.busy_timeout)?;
+        self.state = PowerState::Off;
+        Ok(())
+    }
+
+    /// Powers off and puts the controller in deep sleep. The next operation
+    /// resets and re-initializes the panel.
+    pub fn deep_sleep(&mut self) -> Result<(), DisplayError> {
+        if matches!(self.state, PowerState::DeepSleep | PowerState::Uninitialized) {
+            return Ok(());
+        }
+
+        self.power_off()?;
+
+        info!("Display deep sleep");
+        // BUSY is undefined in deep sleep, nothing to wait on
+        self.cmd_with_data(Command::DeepSleep, &[0xA5])?;
+        self.state = PowerState::DeepSleep;
+        Ok(())
     }
 
-    pub fn init(&mut self) -> Result<(), EspError> {
+    pub fn init(&mut self) -> Result<(), DisplayError> {
         info!("Initializing display");
+        self.state = PowerState::Uninitialized;
 
         self.reset()?;
         info!("Display reset");
@@ -66,16 +172,53 @@ impl DisplayDriver
 
         self.cmd_with_data(Command::TconSetting, &[0x22])?;
 
+        // Use the on-glass sensor so the waveform matches the panel temperature
+        self.cmd_with_data(Command::TemperatureCalibration, &[0x00])?;
+
+        self.state = PowerState::On;
         info!("Display initialized");
 
         Ok(())
     }
 
-    fn wait_until_idle(&mut self) -> Result<(), EspError> {
+    pub fn status(&mut self) -> Result<PanelStatus, DisplayError> {
+        let mut data = [0_u8; 1];
+        self.read(Command::GetStatus, &mut data)?;
+        Ok(PanelStatus::from(data[0]))
+    }
+
+    pub fn revision(&mut self) -> Result<Revision, DisplayError> {
+        let mut data = [0_u8; 3];
+        self.read(Command::Revision, &mut data)?;
+        Ok(Revision::from(data))
+    }
+
+    /// Temperature measured by the panel in degrees Celsius.
+    /// The panel must be powered on for the sensor to be active.
+    pub fn temperature(&mut self) -> Result<f32, DisplayError> {
+        let mut data = [0_u8; 2];
+        self.read(Command::TemperatureSensor, &mut data)?;
+        Ok(temperature_from_raw(data))
+    }
+
+    /// Whether the panel supply voltage is below the low power threshold.
+    pub fn is_low_power(&mut self) -> Result<bool, DisplayError> {
+        let mut data = [0_u8; 1];
+        self.read(Command::LowPowerDetection, &mut data)?;
+        // LPD bit is cleared on low power
+        Ok(data[0] & 0x01 == 0)
+    }
+
+    fn wait_until_idle(&mut self, timeout: Duration) -> Result<(), DisplayError> {
         info!("Waiting for display to become idle");
         self.cmd(Command::GetStatus)?;
 
+        let started = Instant::now();
         while self.is_busy() {
+            if started.elapsed() > timeout {
+                error!("Display still busy after {:?}", timeout);
+                return Err(DisplayError::BusyTimeout(timeout));
+            }
             if !self.config.delay.is_zero() {
                 Delay::delay_ms(self.config.delay.as_millis() as u32);
             }
@@ -85,36 +228,54 @@ impl DisplayDriver
         Ok(())
     }
 
-    fn cmd_with_data<T: traits::Command>(&mut self, cmd: T, data: &[u8]) -> Result<(), EspError> {
+    fn cmd_with_data<T: traits::Command>(&mut self, cmd: T, data: &[u8]) -> Result<(), DisplayError> {
         self.cmd(cmd)?;
         self.data(data)
     }
 
-    fn cmd<T: traits::Command>(&mut self, cmd: T) -> Result<(), EspError> {
+    fn cmd<T: traits::Command>(&mut self, cmd: T) -> Result<(), DisplayError> {
         // low for commands
-        let _ = self.pins.dc.set_low()?;
+        self.pins.dc.set_low().map_err(DisplayError::Gpio)?;
         self.write(&[cmd.address()])
     }
 
-    fn data(&mut self, data: &[u8]) -> Result<(), EspError> {
+    fn data(&mut self, data: &[u8]) -> Result<(), DisplayError> {
         // high for data
-        let _ = self.pins.dc.set_high()?;
+        self.pins.dc.set_high().map_err(DisplayError::Gpio)?;
         self.write(data)
     }
 
-    fn write(&mut self, data: &[u8]) -> Result<(), EspError> {
-        self.pins.cs.set_low()?;
-        self.spi.write(data)?;
-        self.pins.cs.set_high()
+    fn write(&mut self, data: &[u8]) -> Result<(), DisplayError> {
+        self.pins.cs.set_low().map_err(DisplayError::Gpio)?;
+        self.spi.write(data).map_err(DisplayError::Spi)?;
+        self.pins.cs.set_high().map_err(DisplayError::Gpio)
+    }
+
+    fn read<T: traits::Command>(&mut self, cmd: T, buf: &mut [u8]) -> Result<(), DisplayError> {
+        if self.config.read_mode == ReadMode::WriteOnly {
+            return Err(DisplayError::ReadUnsupported);
+        }
+        if matches!(self.state, PowerState::Uninitialized | PowerState::DeepSleep) {
+            self.init()?;
+        }
+
+        // CS has to stay low between the command and the data phase
+        self.pins.cs.set_low().map_err(DisplayError::Gpio)?;
+        self.pins.dc.set_low().map_err(DisplayError::Gpio)?;
+        self.spi.write(&[cmd.address()]).map_err(DisplayError::Spi)?;
+        self.pins.dc.set_high().map_err(DisplayError::Gpio)?;
+        self.spi.read(buf).map_err(DisplayError::Spi)?;
+        self.pins.cs.set_high().map_err(DisplayError::Gpio)
     }
 
     fn is_busy(&self) -> bool {
         self.pins.busy.is_low()
     }
 
-    pub fn clear_screen(&mut self) -> Result<(), EspError> {
+    pub fn clear_screen(&mut self) -> Result<(), DisplayError> {
 
         info!("Clearing screen");
+        self.power_on()?;
 
         self.cmd_with_data(Command::DataStartTransmission1, &[0x00; BUFFER_SIZE])?;
         self.cmd_with_data(Command::DataStartTransmission2, &[0x00; BUFFER_SIZE])?;
@@ -122,14 +283,14 @@ impl DisplayDriver
         self.refresh()
     }
 
-    pub fn reset(&mut self) -> Result<(), EspError> {
-        let _ = self.pins.rst.set_high()?;
+    pub fn reset(&mut self) -> Result<(), DisplayError> {
+        self.pins.rst.set_high().map_err(DisplayError::Gpio)?;
         Delay::delay_ms(200);
 
-        let _ = self.pins.rst.set_low()?;
+        self.pins.rst.set_low().map_err(DisplayError::Gpio)?;
         Delay::delay_ms(2);
 
-        let _ = self.pins.rst.set_high()?;
+        self.pins.rst.set_high().map_err(DisplayError::Gpio)?;
         Delay::delay_ms(200);
 
         Ok(())
@@ -0,0 +1,459 @@
+use core::marker::PhantomData;
+use embedded_graphics::{
+    geometry::*,
+    image::ImageDrawable,
+    Pixel,
+    pixelcolor::*,
+    pixelcolor::raw::RawU32,
+    prelude::*,
+    primitives::Rectangle,
+};
+
+const THRESHOLD: i32 = 128;
+
+/// Ordered dithering threshold map, values 0..64.
+const BAYER_8X8: [[u8; 8]; 8] = [
+    [0, 32, 8, 40, 2, 34, 10, 42],
+    [48, 16, 56, 24, 50, 18, 58, 26],
+    [12, 44, 4, 36, 14, 46, 6, 38],
+    [60, 28, 52, 20, 62, 30, 54, 22],
+    [3, 35, 11, 43, 1, 33, 9, 41],
+    [51, 19, 59, 27, 49, 17, 57, 25],
+    [15, 47, 7, 39, 13, 45, 5, 37],
+    [63, 31, 55, 23, 61, 29, 53, 21],
+];
+
+#[allow(dead_code)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DitherMode {
+    /// Plain threshold at 50% luminance, same as `color_converted()`.
+    Threshold,
+    /// Error diffusion over 4 neighbours. Best at preserving gradients.
+    FloydSteinberg,
+    /// Error diffusion over 6 neighbours, only 3/4 of the error is propagated.
+    /// Keeps thin strokes crisp and highlights clean, which suits icons.
+    Atkinson,
+    /// 8x8 ordered dithering. No error propagation, so no speckles around edges.
+    Bayer,
+}
+
+/// 32 bit color with alpha channel.
+///
+/// Sources using this color are composited against the background color of the
+/// [`Dithered`] adapter before being dithered.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub struct Rgba8888 {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Rgba8888 {
+    pub const fn new(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Rgba8888 { r, g, b, a }
+    }
+
+    /// BT.601 luma
+    fn luma(&self) -> u8 {
+        ((77 * self.r as u32 + 150 * self.g as u32 + 29 * self.b as u32) >> 8) as u8
+    }
+}
+
+impl PixelColor for Rgba8888 {
+    type Raw = RawU32;
+}
+
+/// RGBA byte order, so `ImageRaw<Rgba8888>` reads RGBA buffers
+impl From<RawU32> for Rgba8888 {
+    fn from(raw: RawU32) -> Self {
+        let [r, g, b, a] = raw.into_inner().to_be_bytes();
+        Rgba8888::new(r, g, b, a)
+    }
+}
+
+impl From<Rgb888> for Rgba8888 {
+    fn from(color: Rgb888) -> Self {
+        Rgba8888::new(color.r(), color.g(), color.b(), 0xFF)
+    }
+}
+
+impl From<Gray8> for Rgba8888 {
+    fn from(color: Gray8) -> Self {
+        Rgba8888::new(color.luma(), color.luma(), color.luma(), 0xFF)
+    }
+}
+
+impl From<BinaryColor> for Rgba8888 {
+    fn from(color: BinaryColor) -> Self {
+        match color {
+            BinaryColor::Off => Rgba8888::new(0, 0, 0, 0xFF),
+            BinaryColor::On => Rgba8888::new(0xFF, 0xFF, 0xFF, 0xFF),
+        }
+    }
+}
+
+/// Adapter converting any [`ImageDrawable`] to [`BinaryColor`] using dithering.
+///
+/// Polarity follows `color_converted()`: bright source pixels become `BinaryColor::On`.
+/// Fully transparent pixels are not drawn, partially transparent ones are blended
+/// with `background` first.
+pub struct Dithered<'a, T> {
+    image: &'a T,
+    mode: DitherMode,
+    background: BinaryColor,
+}
+
+#[allow(dead_code)]
+impl<'a, T> Dithered<'a, T>
+    where
+        T: ImageDrawable,
+        T::Color: Into<Rgba8888>,
+{
+    pub fn new(image: &'a T, mode: DitherMode) -> Self {
+        Dithered {
+            image,
+            mode,
+            background: BinaryColor::Off,
+        }
+    }
+
+    pub fn with_background(mut self, background: BinaryColor) -> Self {
+        self.background = background;
+        self
+    }
+
+    /// Dithers the whole image, handing every finished row to `emit`.
+    ///
+    /// Rows are emitted one at a time so only the luminance of the source and
+    /// three rows of error have to be kept around.
+    fn dither<E>(&self, mut emit: impl FnMut(&[Pixel<BinaryColor>]) -> Result<(), E>) -> Result<(), E> {
+        let size = self.image.size();
+        let width = size.width as usize;
+        let height = size.height as usize;
+
+        let mut capture = Capture::<T::Color>::new(size, self.background);
+        // Capture is infallible
+        let _ = self.image.draw(&mut capture);
+
+        // Error for the current row and the two below it. Offset by 2 on each side
+        // so that the kernels never need bound checks.
+        let stride = width + 4;
+        let mut errors = vec![0_i32; stride * 3];
+        let mut row = Vec::with_capacity(width);
+
+        for y in 0..height {
+            row.clear();
+            for x in 0..width {
+                let index = y * width + x;
+                let value = capture.luma[index] as i32 + errors[x + 2];
+
+                let on = match self.mode {
+                    DitherMode::Threshold => value >= THRESHOLD,
+                    DitherMode::Bayer => {
+                        let level = BAYER_8X8[y % 8][x % 8] as i32 * 4 + 2;
+                        value > level
+                    }
+                    DitherMode::FloydSteinberg | DitherMode::Atkinson => value >= THRESHOLD,
+                };
+
+                let error = value - if on { 255 } else { 0 };
+                match self.mode {
+                    DitherMode::FloydSteinberg => {
+                        errors[x + 3] += error * 7 / 16;
+                        errors[stride + x + 1] += error * 3 / 16;
+                        errors[stride + x + 2] += error * 5 / 16;
+                        errors[stride + x + 3] += error / 16;
+                    }
+                    DitherMode::Atkinson => {
+                        let share = error / 8;
+                        errors[x + 3] += share;
+                        errors[x + 4] += share;
+                        errors[stride + x + 1] += share;
+                        errors[stride + x + 2] += share;
+                        errors[stride + x + 3] += share;
+                        errors[2 * stride + x + 2] += share;
+                    }
+                    DitherMode::Threshold | DitherMode::Bayer => {}
+                }
+
+                if !capture.is_transparent(index) {
+                    let color = if on { BinaryColor::On } else { BinaryColor::Off };
+                    row.push(Pixel(Point::new(x as i32, y as i32), color));
+                }
+            }
+
+            // Shift error rows up
+            errors.copy_within(stride.., 0);
+            let last = errors.len() - stride;
+            errors[last..].fill(0);
+
+            emit(&row)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<T: OriginDimensions> OriginDimensions for Dithered<'_, T> {
+    fn size(&self) -> Size {
+        self.image.size()
+    }
+}
+
+impl<T> ImageDrawable for Dithered<'_, T>
+    where
+        T: ImageDrawable,
+        T::Color: Into<Rgba8888>,
+{
+    type Color = BinaryColor;
+
+    fn draw<D>(&self, target: &mut D) -> Result<(), D::Error>
+        where
+            D: DrawTarget<Color = Self::Color>,
+    {
+        self.dither(|row| target.draw_iter(row.iter().copied()))
+    }
+
+    fn draw_sub_image<D>(&self, target: &mut D, area: &Rectangle) -> Result<(), D::Error>
+        where
+            D: DrawTarget<Color = Self::Color>,
+    {
+        // The error diffusion depends on every pixel before it, dither everything
+        // and only keep the requested area so results match `draw`.
+        self.dither(|row| {
+            target.draw_iter(
+                row.iter()
+                    .filter(|Pixel(point, _)| area.contains(*point))
+                    .map(|Pixel(point, color)| Pixel(*point - area.top_left, *color)),
+            )
+        })
+    }
+}
+
+/// Draw target collecting the composited luma of an image.
+struct Capture<C> {
+    size: Size,
+    background: u8,
+    luma: Vec<u8>,
+    /// One bit per pixel, set for pixels with an alpha of 0 or never drawn.
+    transparent: Vec<u8>,
+    color: PhantomData<C>,
+}
+
+impl<C> Capture<C> {
+    fn new(size: Size, background: BinaryColor) -> Self {
+        let len = (size.width * size.height) as usize;
+        let background = match background {
+            BinaryColor::Off => 0,
+            BinaryColor::On => 0xFF,
+        };
+
+        Capture {
+            size,
+            background,
+            luma: vec![background; len],
+            transparent: vec![0xFF; len.div_ceil(8)],
+            color: PhantomData,
+        }
+    }
+
+    fn is_transparent(&self, index: usize) -> bool {
+        self.transparent[index / 8] & (0x80 >> (index % 8)) != 0
+    }
+
+    fn set(&mut self, index: usize, color: Rgba8888) {
+        let mask = 0x80 >> (index % 8);
+        if color.a == 0 {
+            self.transparent[index / 8] |= mask;
+            self.luma[index] = self.background;
+            return;
+        }
+        self.transparent[index / 8] &= !mask;
+
+        let alpha = color.a as u32;
+        let blended = (color.luma() as u32 * alpha + self.background as u32 * (0xFF - alpha)) / 0xFF;
+        self.luma[index] = blended as u8;
+    }
+}
+
+impl<C> OriginDimensions for Capture<C> {
+    fn size(&self) -> Size {
+        self.size
+    }
+}
+
+impl<C> DrawTarget for Capture<C>
+    where
+        C: PixelColor + Into<Rgba8888>,
+{
+    type Color = C;
+    type Error = core::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+        where
+            I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        let bounds = self.bounding_box();
+        for Pixel(point, color) in pixels {
+            if bounds.contains(point) {
+                let index = (point.x as u32 + point.y as u32 * self.size.width) as usize;
+                self.set(index, color.into());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use embedded_graphics::image::{Image, ImageRaw};
+    use embedded_graphics::mock_display::MockDisplay;
+    use super::*;
+
+    const WIDTH: u32 = 16;
+    const HEIGHT: u32 = 8;
+
+    /// Horizontal ramp from black to white, 17 levels apart
+    fn gradient() -> Vec<u8> {
+        (0..HEIGHT).flat_map(|_| (0..WIDTH).map(|x| (x * 17) as u8)).collect()
+    }
+
+    fn render(mode: DitherMode) -> MockDisplay<BinaryColor> {
+        let data = gradient();
+        let raw = ImageRaw::<Gray8>::new(&data, WIDTH);
+        let mut display = MockDisplay::new();
+        Image::new(&Dithered::new(&raw, mode), Point::zero())
+            .draw(&mut display)
+            .unwrap();
+        display
+    }
+
+    #[test]
+    fn threshold() {
+        render(DitherMode::Threshold).assert_pattern(&[
+            "........########",
+            "........########",
+            "........########",
+            "........########",
+            "........########",
+            "........########",
+            "........########",
+            "........########",
+        ]);
+    }
+
+    #[test]
+    fn floyd_steinberg() {
+        render(DitherMode::FloydSteinberg).assert_pattern(&[
+            "......#.#.######",
+            "....#..#.##.####",
+            ".....#.#.#.#####",
+            "...#..#.####.###",
+            "....#..#..#.####",
+            ".....#.#.#######",
+            "...#..#.#.#.####",
+            ".....#.#.####.##",
+        ]);
+    }
+
+    #[test]
+    fn atkinson() {
+        render(DitherMode::Atkinson).assert_pattern(&[
+            ".......##.######",
+            ".....#..########",
+            ".....##..#.#####",
+            ".......###.#####",
+            "....#..#..######",
+            ".....#..###.####",
+            ".....#..########",
+            "......##..######",
+        ]);
+    }
+
+    #[test]
+    fn bayer() {
+        render(DitherMode::Bayer).assert_pattern(&[
+            "....#.#.########",
+            ".....#.#.#.#####",
+            "..#.#.#.#.######",
+            ".......#.#.#.###",
+            "....#.#.########",
+            ".....#.#.#.#####",
+            "..#.#.#.#.######",
+            ".......#.#.#.###",
+        ]);
+    }
+
+    #[test]
+    fn sub_image_matches_full_image() {
+        let data = gradient();
+        let raw = ImageRaw::<Gray8>::new(&data, WIDTH);
+        let area = Rectangle::new(Point::new(4, 2), Size::new(8, 4));
+
+        for mode in [DitherMode::Threshold, DitherMode::FloydSteinberg, DitherMode::Atkinson, DitherMode::Bayer] {
+            let full = render(mode);
+            let mut expected = MockDisplay::new();
+            for point in Rectangle::new(Point::zero(), area.size).points() {
+                expected.set_pixel(point, full.get_pixel(point + area.top_left));
+            }
+
+            let dithered = Dithered::new(&raw, mode);
+            let mut display = MockDisplay::new();
+            Image::new(&dithered.sub_image(&area), Point::zero())
+                .draw(&mut display)
+                .unwrap();
+            display.assert_eq(&expected);
+        }
+    }
+
+    /// `color` over a horizontal alpha ramp, fully transparent on the left
+    fn translucent(color: u8) -> Vec<u8> {
+        (0..HEIGHT).flat_map(|_| (0..WIDTH).flat_map(move |x| [color, color, color, (x * 17) as u8])).collect()
+    }
+
+    fn render_over(color: u8, background: BinaryColor, mode: DitherMode) -> MockDisplay<BinaryColor> {
+        let data = translucent(color);
+        let raw = ImageRaw::<Rgba8888>::new(&data, WIDTH);
+        let mut display = MockDisplay::new();
+        Image::new(&Dithered::new(&raw, mode).with_background(background), Point::zero())
+            .draw(&mut display)
+            .unwrap();
+        display
+    }
+
+    #[test]
+    fn alpha_blends_with_background() {
+        // White fading in over black is the same ramp as the opaque gradient
+        let expected = render(DitherMode::FloydSteinberg);
+        let display = render_over(0xFF, BinaryColor::Off, DitherMode::FloydSteinberg);
+        for point in Rectangle::new(Point::new(1, 0), Size::new(WIDTH - 1, HEIGHT)).points() {
+            assert_eq!(display.get_pixel(point), expected.get_pixel(point), "{:?}", point);
+        }
+
+        render_over(0xFF, BinaryColor::On, DitherMode::Threshold).assert_pattern(&[
+            " ###############",
+            " ###############",
+            " ###############",
+            " ###############",
+            " ###############",
+            " ###############",
+            " ###############",
+            " ###############",
+        ]);
+    }
+
+    #[test]
+    fn translucent_black_over_white() {
+        render_over(0x00, BinaryColor::On, DitherMode::Atkinson).assert_pattern(&[
+            " ######..#......",
+            " ####.##........",
+            " ####..##.#.....",
+            " ######...#.....",
+            " ###.##.##......",
+            " ####.##...#....",
+            " ####.##........",
+            " #####..##......",
+        ]);
+    }
+}
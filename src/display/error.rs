@@ -0,0 +1,36 @@
+use std::fmt;
+use std::time::Duration;
+use esp_idf_sys::EspError;
+
+#[derive(Debug)]
+pub enum DisplayError {
+    /// The panel kept BUSY asserted for longer than the configured timeout.
+    /// Usually means the panel is disconnected or damaged.
+    BusyTimeout(Duration),
+    /// Writing to the SPI bus failed.
+    Spi(EspError),
+    /// Driving or reading one of the control pins failed.
+    Gpio(EspError),
+    /// Reading from the panel was requested but the bus is wired write-only.
+    ReadUnsupported,
+}
+
+impl fmt::Display for DisplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DisplayError::BusyTimeout(timeout) => write!(f, "display still busy after {} ms", timeout.as_millis()),
+            DisplayError::Spi(err) => write!(f, "SPI transfer failed: {}", err),
+            DisplayError::Gpio(err) => write!(f, "GPIO operation failed: {}", err),
+            DisplayError::ReadUnsupported => write!(f, "display bus is not wired for reading"),
+        }
+    }
+}
+
+impl std::error::Error for DisplayError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DisplayError::BusyTimeout(_) | DisplayError::ReadUnsupported => None,
+            DisplayError::Spi(err) | DisplayError::Gpio(err) => Some(err),
+        }
+    }
+}
@@ -1,4 +1,8 @@
 pub mod display;
 pub mod display_driver;
 pub mod command;
-pub mod traits;
\ No newline at end of file
+pub mod traits;
+pub mod dither;
+pub mod diff;
+pub mod error;
+pub mod status;
\ No newline at end of file
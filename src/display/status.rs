@@ -0,0 +1,57 @@
+/// IC status flags as returned by `Command::GetStatus`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PanelStatus {
+    /// Partial display mode is active
+    pub partial: bool,
+    /// The last access to the external temperature sensor failed
+    pub i2c_error: bool,
+    /// The I2C master is idle
+    pub i2c_idle: bool,
+    /// Data received since the last `DataStop` is complete
+    pub data_flag: bool,
+    /// Power on sequence has completed
+    pub powered_on: bool,
+    /// Power off sequence has completed
+    pub powered_off: bool,
+    /// Driver is idle. Mirrors the BUSY pin.
+    pub idle: bool,
+}
+
+impl From<u8> for PanelStatus {
+    fn from(flags: u8) -> Self {
+        PanelStatus {
+            partial: flags & 0x40 != 0,
+            i2c_error: flags & 0x20 != 0,
+            i2c_idle: flags & 0x10 != 0,
+            data_flag: flags & 0x08 != 0,
+            powered_on: flags & 0x04 != 0,
+            powered_off: flags & 0x02 != 0,
+            idle: flags & 0x01 != 0,
+        }
+    }
+}
+
+/// LUT and chip revision as returned by `Command::Revision`
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Revision {
+    pub lut: u16,
+    pub chip: u8,
+}
+
+impl From<[u8; 3]> for Revision {
+    fn from(data: [u8; 3]) -> Self {
+        Revision {
+            lut: u16::from_be_bytes([data[0], data[1]]),
+            chip: data[2],
+        }
+    }
+}
+
+/// Converts the 11 bit two's complement reading of `Command::TemperatureSensor`
+/// to degrees Celsius. One LSB is 0.125°C.
+pub fn temperature_from_raw(data: [u8; 2]) -> f32 {
+    let raw = ((data[0] as u16) << 3) | (data[1] as u16 >> 5);
+    // Sign extend from 11 bits
+    let value = ((raw << 5) as i16) >> 5;
+    value as f32 * 0.125
+}
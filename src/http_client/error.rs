@@ -0,0 +1,36 @@
+use std::fmt;
+use std::time::Duration;
+
+#[derive(Debug)]
+pub enum HttpError {
+    /// The server answered with a status outside of 2xx.
+    Status { status: u16, url: String },
+    /// The body is larger than the client accepts, in bytes.
+    BodyTooLarge(usize),
+    /// The whole request took longer than the configured timeout.
+    TimedOut(Duration),
+}
+
+impl HttpError {
+    /// Whether sending the same request again may succeed
+    pub fn is_transient(&self) -> bool {
+        match self {
+            // Rate limited or server side failures
+            HttpError::Status { status, .. } => *status == 429 || *status >= 500,
+            HttpError::BodyTooLarge(_) => false,
+            HttpError::TimedOut(_) => true,
+        }
+    }
+}
+
+impl fmt::Display for HttpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HttpError::Status { status, url } => write!(f, "unexpected response code {} from {}", status, url),
+            HttpError::BodyTooLarge(limit) => write!(f, "response body larger than {} bytes", limit),
+            HttpError::TimedOut(timeout) => write!(f, "request still running after {} s", timeout.as_secs()),
+        }
+    }
+}
+
+impl std::error::Error for HttpError {}
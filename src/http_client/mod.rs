@@ -1,37 +1,211 @@
-use std::str;
-use anyhow::{Result, bail};
-use embedded_svc::http::client::Client;
-use embedded_svc::io::Read;
+//! HTTPS client with timeouts, a body size limit and retries.
+//!
+//! Bodies are streamed through `Body`, a `std::io::Read`, so large responses
+//! are parsed as they arrive instead of being buffered whole.
+
+mod error;
+
+use std::io::{self, BufReader, Read};
+use std::thread;
+use std::time::{Duration, Instant};
+use anyhow::Result;
+use embedded_svc::http::Method;
 use esp_idf_svc::http::client::{Configuration, EspHttpConnection};
+use log::*;
+use serde::de::DeserializeOwned;
+
+pub use error::HttpError;
+
+const USER_AGENT: &str = concat!("weather-station/", env!("CARGO_PKG_VERSION"));
+/// Bytes read from the connection at once
+const CHUNK_SIZE: usize = 1024;
+
+/// How many times a request is attempted, waiting twice as long after each failure
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub attempts: u32,
+    pub backoff: Duration,
+}
+
+#[allow(dead_code)]
+impl RetryPolicy {
+    pub const NONE: RetryPolicy = RetryPolicy { attempts: 1, backoff: Duration::ZERO };
+
+    fn delay(&self, attempt: u32) -> Duration {
+        self.backoff * 2_u32.saturating_pow(attempt.saturating_sub(1))
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            attempts: 3,
+            backoff: Duration::from_secs(2),
+        }
+    }
+}
+
+pub struct HttpClient {
+    /// Longest wait for any single network operation
+    read_timeout: Duration,
+    /// Longest time for a whole request, body included
+    request_timeout: Duration,
+    max_body_size: usize,
+    retry: RetryPolicy,
+    headers: Vec<(String, String)>,
+}
+
+#[allow(dead_code)]
+impl HttpClient {
+    pub fn new() -> Self {
+        HttpClient {
+            read_timeout: Duration::from_secs(10),
+            request_timeout: Duration::from_secs(60),
+            max_body_size: 256 * 1024,
+            retry: RetryPolicy::default(),
+            headers: vec![(String::from("User-Agent"), String::from(USER_AGENT))],
+        }
+    }
+
+    pub fn with_read_timeout(mut self, timeout: Duration) -> Self {
+        self.read_timeout = timeout;
+        self
+    }
+
+    pub fn with_request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
+
+    pub fn with_max_body_size(mut self, bytes: usize) -> Self {
+        self.max_body_size = bytes;
+        self
+    }
+
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
 
-pub fn get(url: impl AsRef<str>) -> Result<String> {
-    let connection = EspHttpConnection::new(&Configuration {
-        use_global_ca_store: true,
-        crt_bundle_attach: Some(esp_idf_sys::esp_crt_bundle_attach),
-        ..Default::default()
-    })?;
-
-    let mut client = Client::wrap(connection);
-    let request = client.get(url.as_ref())?;
-
-    let response = request.submit()?;
-    let status = response.status();
-
-    match status {
-        200..=299 => {
-            let mut buf = [0_u8; 256];
-            let mut reader = response;
-            let mut response_text = String::new();
-            loop {
-                if let Ok(size) = Read::read(&mut reader, &mut buf) {
-                    if size == 0 {
-                        break;
-                    }
-                    response_text.push_str(str::from_utf8(&buf[..size])?);
+    /// Sends a GET request and returns the body once the response headers
+    /// arrived. Failures before that are retried according to the policy.
+    pub fn get(&self, url: &str) -> Result<Body> {
+        let mut attempt = 1;
+        loop {
+            match self.send(url) {
+                Ok(body) => return Ok(body),
+                Err(err) if attempt < self.retry.attempts && is_transient(&err) => {
+                    let delay = self.retry.delay(attempt);
+                    warn!("GET {} failed, retrying in {} ms: {:#}", redact(url), delay.as_millis(), err);
+                    thread::sleep(delay);
+                    attempt += 1;
                 }
+                Err(err) => return Err(err),
             }
-            Ok(response_text)
         }
-        _ => bail!("Unexpected response code: {} from {}", status, url.as_ref()),
     }
-}
\ No newline at end of file
+
+    /// Whole body as text, decoded once complete so characters split across chunks survive
+    pub fn get_string(&self, url: &str) -> Result<String> {
+        let mut bytes = Vec::new();
+        self.get(url)?.read_to_end(&mut bytes)?;
+        Ok(String::from_utf8(bytes)?)
+    }
+
+    /// Body deserialized as it is received
+    pub fn get_json<T: DeserializeOwned>(&self, url: &str) -> Result<T> {
+        let body = BufReader::with_capacity(CHUNK_SIZE, self.get(url)?);
+        Ok(serde_json::from_reader(body)?)
+    }
+
+    /// Streams the body to `write` chunk by chunk, returns its size
+    pub fn download(&self, url: &str, mut write: impl FnMut(&[u8]) -> Result<()>) -> Result<usize> {
+        let mut body = self.get(url)?;
+        let mut buf = vec![0_u8; CHUNK_SIZE];
+        loop {
+            let size = body.read(&mut buf)?;
+            if size == 0 {
+                return Ok(body.received);
+            }
+            write(&buf[..size])?;
+        }
+    }
+
+    fn send(&self, url: &str) -> Result<Body> {
+        let deadline = Instant::now() + self.request_timeout;
+        let mut connection = EspHttpConnection::new(&Configuration {
+            use_global_ca_store: true,
+            crt_bundle_attach: Some(esp_idf_sys::esp_crt_bundle_attach),
+            timeout: Some(self.read_timeout),
+            ..Default::default()
+        })?;
+
+        let headers: Vec<(&str, &str)> = self.headers.iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .collect();
+        connection.initiate_request(Method::Get, url, &headers)?;
+        connection.initiate_response()?;
+
+        let status = connection.status();
+        if !(200..=299).contains(&status) {
+            return Err(HttpError::Status { status, url: redact(url) }.into());
+        }
+        let content_length = connection.header("Content-Length").and_then(|length| length.parse::<usize>().ok());
+        if content_length.is_some_and(|length| length > self.max_body_size) {
+            return Err(HttpError::BodyTooLarge(self.max_body_size).into());
+        }
+
+        Ok(Body {
+            connection,
+            received: 0,
+            max_body_size: self.max_body_size,
+            deadline,
+            request_timeout: self.request_timeout,
+        })
+    }
+}
+
+impl Default for HttpClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Body of a successful response
+pub struct Body {
+    connection: EspHttpConnection,
+    received: usize,
+    max_body_size: usize,
+    deadline: Instant,
+    request_timeout: Duration,
+}
+
+impl Read for Body {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if Instant::now() >= self.deadline {
+            return Err(io::Error::new(io::ErrorKind::TimedOut, HttpError::TimedOut(self.request_timeout)));
+        }
+
+        let size = self.connection.read(buf).map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+        self.received += size;
+        if self.received > self.max_body_size {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, HttpError::BodyTooLarge(self.max_body_size)));
+        }
+        Ok(size)
+    }
+}
+
+/// Connection failures and some statuses are worth retrying, the rest won't change
+fn is_transient(err: &anyhow::Error) -> bool {
+    err.downcast_ref::<HttpError>().map_or(true, HttpError::is_transient)
+}
+
+/// `url` without its query, which holds the API key
+fn redact(url: &str) -> String {
+    url.split('?').next().unwrap_or_default().to_string()
+}
@@ -0,0 +1,111 @@
+//! Generates the icon registry from `src/icons/qoi/<size>x<size>/<name>.qoi`.
+//! Shared by `build.rs` and the host tests so both see the same `Icon`s.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+/// Source of `registry.rs`: an `Icon` variant per name and a `const` lookup
+/// of its data by size, covering every size in `root`
+pub fn icon_registry(root: &Path) -> Result<String, Box<dyn Error>> {
+    let root = root.canonicalize()?;
+
+    // Size -> names available in that size
+    let mut sizes: BTreeMap<u32, BTreeSet<String>> = BTreeMap::new();
+    let mut names = BTreeSet::new();
+    for entry in fs::read_dir(&root)? {
+        let dir = entry?.path();
+        let dir_name = dir.file_name().and_then(|name| name.to_str()).unwrap_or_default().to_string();
+        let size = match dir_name.split_once('x') {
+            Some((width, height)) if width == height => width.parse::<u32>()?,
+            _ => return Err(format!("Icon directory {} is not named <size>x<size>", dir.display()).into()),
+        };
+
+        let available = sizes.entry(size).or_default();
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("qoi") {
+                continue;
+            }
+            let name = path.file_stem().and_then(|name| name.to_str()).unwrap_or_default().to_string();
+            names.insert(name.clone());
+            available.insert(name);
+        }
+    }
+
+    let mut variants = BTreeMap::new();
+    for name in &names {
+        let variant = variant_name(name);
+        if let Some(other) = variants.insert(variant.clone(), name) {
+            return Err(format!("Icons {} and {} are both named {}", other, name, variant).into());
+        }
+    }
+
+    let mut code = String::new();
+    writeln!(code, "/// Every icon in `src/icons/qoi`, named after its file")?;
+    writeln!(code, "#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]")?;
+    writeln!(code, "pub enum Icon {{")?;
+    for (variant, name) in &variants {
+        writeln!(code, "    /// `{}`", name)?;
+        writeln!(code, "    {},", variant)?;
+    }
+    writeln!(code, "}}\n")?;
+
+    writeln!(code, "impl Icon {{")?;
+    writeln!(code, "    pub const ALL: &'static [Icon] = &[")?;
+    for variant in variants.keys() {
+        writeln!(code, "        Icon::{},", variant)?;
+    }
+    writeln!(code, "    ];\n")?;
+    writeln!(code, "    /// File name without extension")?;
+    writeln!(code, "    pub const fn name(self) -> &'static str {{")?;
+    writeln!(code, "        match self {{")?;
+    for (variant, name) in &variants {
+        writeln!(code, "            Icon::{} => \"{}\",", variant, name)?;
+    }
+    writeln!(code, "        }}")?;
+    writeln!(code, "    }}")?;
+    writeln!(code, "}}\n")?;
+
+    writeln!(code, "/// Sizes available in `src/icons/qoi`, in pixels")?;
+    let available: Vec<String> = sizes.keys().map(u32::to_string).collect();
+    writeln!(code, "pub const SIZES: &[u32] = &[{}];\n", available.join(", "))?;
+
+    writeln!(code, "/// QOI data of `icon` in `size`, `None` when there is no such file.")?;
+    writeln!(code, "/// Only for `const` items: called at runtime it would put every icon in flash.")?;
+    writeln!(code, "pub const fn data(icon: Icon, size: u32) -> Option<&'static [u8]> {{")?;
+    writeln!(code, "    match (icon, size) {{")?;
+    for (size, available) in &sizes {
+        for name in available {
+            writeln!(
+                code,
+                "        (Icon::{}, {}) => Some(include_bytes!(\"{}/{}x{}/{}.qoi\")),",
+                variant_name(name), size, root.display(), size, size, name,
+            )?;
+        }
+    }
+    writeln!(code, "        _ => None,")?;
+    writeln!(code, "    }}")?;
+    writeln!(code, "}}")?;
+
+    Ok(code)
+}
+
+/// `wi-day-sunny` -> `WiDaySunny`, `wi-wind-22_5deg` -> `WiWind22_5deg`
+fn variant_name(name: &str) -> String {
+    let mut variant = String::new();
+    for part in name.split(['-', '_']).filter(|part| !part.is_empty()) {
+        // Keep numbers like 22_5 apart from 225
+        if variant.ends_with(|char: char| char.is_ascii_digit()) && part.starts_with(|char: char| char.is_ascii_digit()) {
+            variant.push('_');
+        }
+        let mut chars = part.chars();
+        if let Some(first) = chars.next() {
+            variant.push(first.to_ascii_uppercase());
+            variant.extend(chars);
+        }
+    }
+    variant
+}
@@ -0,0 +1,430 @@
+//! Icon data embedded in the firmware.
+//!
+//! Every table is built at compile time from `registry::data`, so only the
+//! icons listed here end up in flash, in the sizes they are listed in. The
+//! build fails when one of them is missing or when they outgrow
+//! `ICON_FLASH_BUDGET`.
+
+use crate::astronomy::moon::PHASE_STEPS;
+use crate::icons::registry::{self, Icon};
+
+/// Flash set aside for icons, the rest of the 0x1F0000 app partition holds the code.
+/// tools/flash.sh checks the whole image fits before flashing it.
+pub const ICON_FLASH_BUDGET: usize = 768 * 1024;
+
+/// Data of `icon` in `size`, fails the build when there is no such file
+pub const fn embed(icon: Icon, size: u32) -> &'static [u8] {
+    match registry::data(icon, size) {
+        Some(data) => data,
+        None => panic!("Icon missing in this size, see src/icons/qoi"),
+    }
+}
+
+const fn embed_all<const N: usize>(icons: &[Icon; N], size: u32) -> [&'static [u8]; N] {
+    let mut data: [&'static [u8]; N] = [&[]; N];
+    let mut index = 0;
+    while index < N {
+        data[index] = embed(icons[index], size);
+        index += 1;
+    }
+    data
+}
+
+const fn by_name(name: &str) -> Icon {
+    match registry::by_name(name) {
+        Some(icon) => icon,
+        None => panic!("Unknown icon"),
+    }
+}
+
+const fn bytes(data: &[&[u8]]) -> usize {
+    let mut total = 0;
+    let mut index = 0;
+    while index < data.len() {
+        total += data[index].len();
+        index += 1;
+    }
+    total
+}
+
+/// Every icon an `IconTheme` can return, fallbacks included
+pub const CONDITION_GLYPHS: [Icon; 57] = [
+    // weather-icons
+    Icon::WiDaySunny,
+    Icon::WiDayWindy,
+    Icon::WiNightClear,
+    Icon::WiCloud,
+    Icon::WiCloudyWindy,
+    Icon::WiDayCloudy,
+    Icon::WiDayCloudyWindy,
+    Icon::WiNightAltCloudy,
+    Icon::WiNightAltCloudyWindy,
+    Icon::WiCloudy,
+    Icon::WiDaySnow,
+    Icon::WiDaySnowWind,
+    Icon::WiNightAltSnow,
+    Icon::WiNightAltSnowWind,
+    Icon::WiSnow,
+    Icon::WiSnowWind,
+    Icon::WiDayRain,
+    Icon::WiDayRainWind,
+    Icon::WiNightAltRain,
+    Icon::WiNightAltRainWind,
+    Icon::WiRain,
+    Icon::WiRainWind,
+    Icon::WiDayRainMix,
+    Icon::WiNightAltRainMix,
+    Icon::WiRainMix,
+    Icon::WiDayShowers,
+    Icon::WiNightAltShowers,
+    Icon::WiShowers,
+    Icon::WiDayThunderstorm,
+    Icon::WiNightAltThunderstorm,
+    Icon::WiThunderstorm,
+    Icon::WiDayFog,
+    Icon::WiNightFog,
+    Icon::WiFog,
+    Icon::WiSmoke,
+    Icon::WiDust,
+    Icon::WiSandstorm,
+    Icon::WiCloudyGusts,
+    Icon::WiTornado,
+    Icon::WiVolcano,
+    Icon::WiNa,
+    // minimal
+    Icon::OutlineCloud,
+    Icon::OutlineDrizzle,
+    Icon::OutlineDust,
+    Icon::OutlineFog,
+    Icon::OutlineMoon,
+    Icon::OutlineNa,
+    Icon::OutlineOvercast,
+    Icon::OutlineRain,
+    Icon::OutlineRainSnow,
+    Icon::OutlineSleet,
+    Icon::OutlineSnow,
+    Icon::OutlineSun,
+    Icon::OutlineThunderstorm,
+    Icon::OutlineTornado,
+    Icon::OutlineVolcano,
+    Icon::OutlineWind,
+];
+
+/// Every condition glyph in one size
+pub struct GlyphSheet {
+    pub size: u32,
+    data: [&'static [u8]; CONDITION_GLYPHS.len()],
+}
+
+impl GlyphSheet {
+    const fn new(size: u32) -> Self {
+        GlyphSheet {
+            size,
+            data: embed_all(&CONDITION_GLYPHS, size),
+        }
+    }
+
+    /// Data of `icon`, `None` when it isn't a condition glyph
+    pub fn get(&self, icon: Icon) -> Option<&'static [u8]> {
+        CONDITION_GLYPHS.iter()
+            .position(|glyph| *glyph == icon)
+            .map(|index| self.data[index])
+    }
+}
+
+/// Sizes condition icons are drawn in, each enabled by its `icons-<size>` feature
+pub const CONDITION_SHEETS: &[GlyphSheet] = &[
+    #[cfg(feature = "icons-16")] GlyphSheet::new(16),
+    #[cfg(feature = "icons-24")] GlyphSheet::new(24),
+    #[cfg(feature = "icons-32")] GlyphSheet::new(32),
+    #[cfg(feature = "icons-48")] GlyphSheet::new(48),
+    #[cfg(feature = "icons-64")] GlyphSheet::new(64),
+    #[cfg(feature = "icons-96")] GlyphSheet::new(96),
+    #[cfg(feature = "icons-128")] GlyphSheet::new(128),
+    #[cfg(feature = "icons-160")] GlyphSheet::new(160),
+    #[cfg(feature = "icons-196")] GlyphSheet::new(196),
+];
+
+/// Sheet of the largest size fitting in a `width`×`height` box, `None` when all are larger
+pub fn fitting(sheets: &[GlyphSheet], width: u32, height: u32) -> Option<&GlyphSheet> {
+    sheets.iter()
+        .filter(|sheet| sheet.size <= width.min(height))
+        .max_by_key(|sheet| sheet.size)
+}
+
+/// Size of the moon phase icons
+pub const MOON_ICON_SIZE: u32 = 96;
+
+/// Filled moon phases, from new moon through the whole lunation
+pub const MOON_PHASES: [Icon; PHASE_STEPS] = [
+    Icon::WiMoonNew,
+    Icon::WiMoonWaxingCrescent1,
+    Icon::WiMoonWaxingCrescent2,
+    Icon::WiMoonWaxingCrescent3,
+    Icon::WiMoonWaxingCrescent4,
+    Icon::WiMoonWaxingCrescent5,
+    // Named differently from the rest of the set
+    Icon::WiMoonWaxing6,
+    Icon::WiMoonFirstQuarter,
+    Icon::WiMoonWaxingGibbous1,
+    Icon::WiMoonWaxingGibbous2,
+    Icon::WiMoonWaxingGibbous3,
+    Icon::WiMoonWaxingGibbous4,
+    Icon::WiMoonWaxingGibbous5,
+    Icon::WiMoonWaxingGibbous6,
+    Icon::WiMoonFull,
+    Icon::WiMoonWaningGibbous1,
+    Icon::WiMoonWaningGibbous2,
+    Icon::WiMoonWaningGibbous3,
+    Icon::WiMoonWaningGibbous4,
+    Icon::WiMoonWaningGibbous5,
+    Icon::WiMoonWaningGibbous6,
+    Icon::WiMoonThirdQuarter,
+    Icon::WiMoonWaningCrescent1,
+    Icon::WiMoonWaningCrescent2,
+    Icon::WiMoonWaningCrescent3,
+    Icon::WiMoonWaningCrescent4,
+    Icon::WiMoonWaningCrescent5,
+    Icon::WiMoonWaningCrescent6,
+];
+
+/// Outlined moon phases, in the same order
+pub const MOON_ALT_PHASES: [Icon; PHASE_STEPS] = [
+    Icon::WiMoonAltNew,
+    Icon::WiMoonAltWaxingCrescent1,
+    Icon::WiMoonAltWaxingCrescent2,
+    Icon::WiMoonAltWaxingCrescent3,
+    Icon::WiMoonAltWaxingCrescent4,
+    Icon::WiMoonAltWaxingCrescent5,
+    Icon::WiMoonAltWaxingCrescent6,
+    Icon::WiMoonAltFirstQuarter,
+    Icon::WiMoonAltWaxingGibbous1,
+    Icon::WiMoonAltWaxingGibbous2,
+    Icon::WiMoonAltWaxingGibbous3,
+    Icon::WiMoonAltWaxingGibbous4,
+    Icon::WiMoonAltWaxingGibbous5,
+    Icon::WiMoonAltWaxingGibbous6,
+    Icon::WiMoonAltFull,
+    Icon::WiMoonAltWaningGibbous1,
+    Icon::WiMoonAltWaningGibbous2,
+    Icon::WiMoonAltWaningGibbous3,
+    Icon::WiMoonAltWaningGibbous4,
+    Icon::WiMoonAltWaningGibbous5,
+    Icon::WiMoonAltWaningGibbous6,
+    Icon::WiMoonAltThirdQuarter,
+    Icon::WiMoonAltWaningCrescent1,
+    Icon::WiMoonAltWaningCrescent2,
+    Icon::WiMoonAltWaningCrescent3,
+    Icon::WiMoonAltWaningCrescent4,
+    Icon::WiMoonAltWaningCrescent5,
+    Icon::WiMoonAltWaningCrescent6,
+];
+
+const MOON: [&[u8]; PHASE_STEPS] = embed_all(&MOON_PHASES, MOON_ICON_SIZE);
+const MOON_ALT: [&[u8]; PHASE_STEPS] = embed_all(&MOON_ALT_PHASES, MOON_ICON_SIZE);
+
+/// Icon of a step of `astronomy::moon::MoonPhase`. `alternate` selects the outlined set.
+pub fn moon_phase_icon(step: usize, alternate: bool) -> Icon {
+    let icons = if alternate { &MOON_ALT_PHASES } else { &MOON_PHASES };
+    icons[step % PHASE_STEPS]
+}
+
+/// Data of `moon_phase_icon`
+pub fn moon_phase(step: usize, alternate: bool) -> &'static [u8] {
+    let data = if alternate { &MOON_ALT } else { &MOON };
+    data[step % PHASE_STEPS]
+}
+
+/// Size of the sunrise and sunset icons
+pub const SUN_ICON_SIZE: u32 = 48;
+
+pub const SUNRISE: &[u8] = embed(Icon::WiSunrise, SUN_ICON_SIZE);
+pub const SUNSET: &[u8] = embed(Icon::WiSunset, SUN_ICON_SIZE);
+
+/// Size of the wind direction and Beaufort icons
+pub const WIND_ICON_SIZE: u32 = 96;
+
+/// Angle between two wind direction arrows. The full one degree set would
+/// take a lot of flash for no visible difference.
+pub const WIND_DIRECTION_STEP: u16 = 5;
+
+macro_rules! wind_directions {
+    ($($angle:literal),* $(,)?) => {
+        [$(by_name(concat!("wind_direction_meteorological_", $angle, "deg")),)*]
+    };
+}
+
+/// Arrows for meteorological wind directions, i.e. where the wind comes from
+const WIND_DIRECTIONS: [Icon; 72] = wind_directions![
+    0, 5, 10, 15, 20, 25, 30, 35, 40, 45, 50, 55,
+    60, 65, 70, 75, 80, 85, 90, 95, 100, 105, 110, 115,
+    120, 125, 130, 135, 140, 145, 150, 155, 160, 165, 170, 175,
+    180, 185, 190, 195, 200, 205, 210, 215, 220, 225, 230, 235,
+    240, 245, 250, 255, 260, 265, 270, 275, 280, 285, 290, 295,
+    300, 305, 310, 315, 320, 325, 330, 335, 340, 345, 350, 355,
+];
+const WIND_DIRECTION: [&[u8]; 72] = embed_all(&WIND_DIRECTIONS, WIND_ICON_SIZE);
+
+/// Arrow of `degrees`, rounded to the closest `WIND_DIRECTION_STEP`
+pub fn wind_direction(degrees: u16) -> &'static [u8] {
+    let step = (degrees % 360 + WIND_DIRECTION_STEP / 2) / WIND_DIRECTION_STEP;
+    WIND_DIRECTION[step as usize % WIND_DIRECTION.len()]
+}
+
+const BEAUFORT_FORCES: [Icon; 13] = [
+    Icon::WiWindBeaufort0,
+    Icon::WiWindBeaufort1,
+    Icon::WiWindBeaufort2,
+    Icon::WiWindBeaufort3,
+    Icon::WiWindBeaufort4,
+    Icon::WiWindBeaufort5,
+    Icon::WiWindBeaufort6,
+    Icon::WiWindBeaufort7,
+    Icon::WiWindBeaufort8,
+    Icon::WiWindBeaufort9,
+    Icon::WiWindBeaufort10,
+    Icon::WiWindBeaufort11,
+    Icon::WiWindBeaufort12,
+];
+const BEAUFORT: [&[u8]; 13] = embed_all(&BEAUFORT_FORCES, WIND_ICON_SIZE);
+
+/// Icon of a Beaufort force, 0 to 12
+pub fn beaufort(force: u8) -> &'static [u8] {
+    BEAUFORT[(force as usize).min(BEAUFORT.len() - 1)]
+}
+
+const fn condition_bytes(sheets: &[GlyphSheet]) -> usize {
+    let mut total = 0;
+    let mut index = 0;
+    while index < sheets.len() {
+        total += bytes(&sheets[index].data);
+        index += 1;
+    }
+    total
+}
+
+/// Icon data in flash
+pub const ICON_BYTES: usize = condition_bytes(CONDITION_SHEETS)
+    + bytes(&MOON) + bytes(&MOON_ALT)
+    + SUNRISE.len() + SUNSET.len()
+    + bytes(&WIND_DIRECTION) + bytes(&BEAUFORT);
+
+const _: () = assert!(ICON_BYTES <= ICON_FLASH_BUDGET, "Icons exceed ICON_FLASH_BUDGET, disable an icons-<size> feature");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::astronomy::moon::MoonPhase;
+
+    fn assert_decodes(data: &[u8], size: u32) {
+        let (header, _) = qoi::decode_to_vec(data).unwrap();
+        assert_eq!((header.width, header.height), (size, size));
+    }
+
+    #[test]
+    fn embedded_icons_decode_in_their_size() {
+        for sheet in CONDITION_SHEETS {
+            for data in sheet.data {
+                assert_decodes(data, sheet.size);
+            }
+        }
+        for data in MOON.iter().chain(&MOON_ALT) {
+            assert_decodes(data, MOON_ICON_SIZE);
+        }
+        for data in [SUNRISE, SUNSET] {
+            assert_decodes(data, SUN_ICON_SIZE);
+        }
+        for data in WIND_DIRECTION.iter().chain(&BEAUFORT) {
+            assert_decodes(data, WIND_ICON_SIZE);
+        }
+    }
+
+    #[test]
+    fn sheets_find_condition_glyphs_only() {
+        for sheet in CONDITION_SHEETS {
+            assert_eq!(sheet.get(Icon::WiNa), Some(embed(Icon::WiNa, sheet.size)));
+            assert_eq!(sheet.get(Icon::WiMoonFull), None);
+        }
+    }
+
+    #[test]
+    fn largest_sheet_fitting_the_box_is_picked() {
+        let sheets = [GlyphSheet::new(16), GlyphSheet::new(64), GlyphSheet::new(196)];
+        let size = |width, height| fitting(&sheets, width, height).map(|sheet| sheet.size);
+        assert_eq!(size(196, 196), Some(196));
+        assert_eq!(size(500, 300), Some(196));
+        assert_eq!(size(195, 400), Some(64));
+        assert_eq!(size(64, 64), Some(64));
+        assert_eq!(size(48, 100), Some(16));
+        assert_eq!(size(15, 15), None);
+    }
+
+    #[test]
+    fn moon_phases_map_to_their_glyph() {
+        for (phase, filled, outline) in [
+            (0.0, Icon::WiMoonNew, Icon::WiMoonAltNew),
+            (0.017, Icon::WiMoonNew, Icon::WiMoonAltNew),
+            (0.018, Icon::WiMoonWaxingCrescent1, Icon::WiMoonAltWaxingCrescent1),
+            (0.2, Icon::WiMoonWaxing6, Icon::WiMoonAltWaxingCrescent6),
+            (0.25, Icon::WiMoonFirstQuarter, Icon::WiMoonAltFirstQuarter),
+            (0.3, Icon::WiMoonWaxingGibbous1, Icon::WiMoonAltWaxingGibbous1),
+            (0.5, Icon::WiMoonFull, Icon::WiMoonAltFull),
+            (0.6, Icon::WiMoonWaningGibbous3, Icon::WiMoonAltWaningGibbous3),
+            (0.75, Icon::WiMoonThirdQuarter, Icon::WiMoonAltThirdQuarter),
+            (0.9, Icon::WiMoonWaningCrescent4, Icon::WiMoonAltWaningCrescent4),
+            (0.99, Icon::WiMoonNew, Icon::WiMoonAltNew),
+            (1.0, Icon::WiMoonNew, Icon::WiMoonAltNew),
+        ] {
+            let step = MoonPhase::new(phase).step();
+            assert_eq!(moon_phase_icon(step, false), filled, "phase {}", phase);
+            assert_eq!(moon_phase_icon(step, true), outline, "phase {}", phase);
+            assert_eq!(moon_phase(step, false), embed(filled, MOON_ICON_SIZE));
+            assert_eq!(moon_phase(step, true), embed(outline, MOON_ICON_SIZE));
+        }
+    }
+
+    #[test]
+    fn moon_sets_follow_the_same_sequence() {
+        // Both sets name their glyphs after the phase, bar the filled waxing crescent 6
+        for (filled, outline) in MOON_PHASES.iter().zip(MOON_ALT_PHASES) {
+            let expected = outline.name().replace("wi-moon-alt-", "wi-moon-");
+            let expected = if expected == "wi-moon-waxing-crescent-6" { "wi-moon-waxing-6".to_string() } else { expected };
+            assert_eq!(filled.name(), expected);
+        }
+    }
+
+    #[test]
+    fn wind_direction_rounds_to_the_closest_arrow() {
+        let arrow = |name: &str| embed(registry::by_name(name).unwrap(), WIND_ICON_SIZE);
+        for (degrees, expected) in [
+            (0, "wind_direction_meteorological_0deg"),
+            (2, "wind_direction_meteorological_0deg"),
+            (3, "wind_direction_meteorological_5deg"),
+            (92, "wind_direction_meteorological_90deg"),
+            (357, "wind_direction_meteorological_355deg"),
+            (358, "wind_direction_meteorological_0deg"),
+            (360, "wind_direction_meteorological_0deg"),
+            (722, "wind_direction_meteorological_0deg"),
+        ] {
+            assert_eq!(wind_direction(degrees), arrow(expected), "{}°", degrees);
+        }
+    }
+
+    #[test]
+    fn beaufort_forces_above_12_use_the_last_icon() {
+        assert_eq!(beaufort(0), embed(Icon::WiWindBeaufort0, WIND_ICON_SIZE));
+        assert_eq!(beaufort(12), embed(Icon::WiWindBeaufort12, WIND_ICON_SIZE));
+        assert_eq!(beaufort(13), beaufort(12));
+    }
+
+    #[test]
+    fn budget_counts_every_table() {
+        let tables: [&[&[u8]]; 6] = [&MOON, &MOON_ALT, &[SUNRISE], &[SUNSET], &WIND_DIRECTION, &BEAUFORT];
+        let total: usize = CONDITION_SHEETS.iter().map(|sheet| &sheet.data[..])
+            .chain(tables)
+            .flatten()
+            .map(|data| data.len())
+            .sum();
+        assert_eq!(ICON_BYTES, total);
+    }
+}
@@ -0,0 +1,33 @@
+//! Every icon in `src/icons/qoi`, generated by `build.rs`.
+//! Only those listed in `glyphs` are embedded in the firmware.
+
+#![allow(dead_code)]
+
+include!(concat!(env!("OUT_DIR"), "/icons.rs"));
+
+/// Icon named `name`, usable in `const` contexts
+pub const fn by_name(name: &str) -> Option<Icon> {
+    let mut index = 0;
+    while index < Icon::ALL.len() {
+        if str_eq(Icon::ALL[index].name(), name) {
+            return Some(Icon::ALL[index]);
+        }
+        index += 1;
+    }
+    None
+}
+
+const fn str_eq(a: &str, b: &str) -> bool {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    let mut index = 0;
+    while index < a.len() {
+        if a[index] != b[index] {
+            return false;
+        }
+        index += 1;
+    }
+    true
+}
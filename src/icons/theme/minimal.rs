@@ -0,0 +1,51 @@
+use crate::icons::registry::Icon;
+use crate::icons::theme::{IconContext, IconTheme};
+use crate::owm::model::WeatherConditionId;
+
+/// A single outline glyph per kind of weather, from the `outline-*` set.
+/// Only clear skies tell day from night.
+pub struct Minimal;
+
+impl IconTheme for Minimal {
+    fn name(&self) -> &'static str {
+        "minimal"
+    }
+
+    fn condition_icon(&self, condition: WeatherConditionId, context: IconContext) -> Option<Icon> {
+        use WeatherConditionId::*;
+
+        let icon = match condition {
+            // Thunderstorm, 2xx
+            ThunderstormWithLightRain | ThunderstormWithRain | ThunderstormWithHeavyRain
+            | LightThunderstorm | Thunderstorm | HeavyThunderstorm | RaggedThunderstorm
+            | ThunderstormWithLightDrizzle | ThunderstormWithDrizzle | ThunderstormWithHeavyDrizzle => Icon::OutlineThunderstorm,
+            // Drizzle, 3xx
+            LightIntensityDrizzle | Drizzle | HeavyIntensityDrizzle | LightIntensityDrizzleRain
+            | DrizzleRain | HeavyIntensityDrizzleRain | ShowerRainAndDrizzle
+            | HeavyShowerRainAndDrizzle | ShowerDrizzle => Icon::OutlineDrizzle,
+            // Rain, 5xx
+            LightRain | ModerateRain | HeavyIntensityRain | VeryHeavyRain | ExtremeRain
+            | LightIntensityShowerRain | ShowerRain | HeavyIntensityShowerRain | RaggedShowerRain => Icon::OutlineRain,
+            // Freezing rain and snow, 511 and 6xx
+            FreezingRain | Sleet | LightShowerSleet | ShowerSleet => Icon::OutlineSleet,
+            LightRainAndSnow | RainAndSnow => Icon::OutlineRainSnow,
+            LightSnow | Snow | HeavySnow | LightShowerSnow | ShowerSnow | HeavyShowerSnow => Icon::OutlineSnow,
+            // Atmosphere, 7xx
+            Mist | Haze | Fog => Icon::OutlineFog,
+            Smoke | SandOrDustWhirls | Sand | Dust => Icon::OutlineDust,
+            VolcanicAsh => Icon::OutlineVolcano,
+            Squalls => Icon::OutlineWind,
+            Tornado => Icon::OutlineTornado,
+            // Clear and clouds, 800 and 80x
+            ClearSky if context.night => Icon::OutlineMoon,
+            ClearSky => Icon::OutlineSun,
+            FewClouds | ScatteredClouds | BrokenClouds => Icon::OutlineCloud,
+            OvercastClouds => Icon::OutlineOvercast,
+        };
+        Some(icon)
+    }
+
+    fn fallback(&self) -> Icon {
+        Icon::OutlineNa
+    }
+}
@@ -0,0 +1,189 @@
+//! Icon themes: how weather conditions map onto the embedded glyphs.
+//!
+//! Widgets only ask a theme for an `Icon`, so a theme can be swapped without
+//! touching any drawing code. New asset sets are imported with `tools/assets`.
+
+mod minimal;
+mod weather_icons;
+
+use std::str::FromStr;
+use anyhow::{bail, Error, Result};
+use crate::icons::registry::Icon;
+use crate::owm::model::WeatherConditionId;
+
+pub use minimal::Minimal;
+pub use weather_icons::WeatherIcons;
+
+/// Circumstances a condition is drawn in
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct IconContext {
+    pub night: bool,
+    pub cloudy: bool,
+    pub windy: bool,
+}
+
+pub trait IconTheme {
+    fn name(&self) -> &'static str;
+
+    /// Glyph of `condition`, `None` when the theme has none for it
+    fn condition_icon(&self, condition: WeatherConditionId, context: IconContext) -> Option<Icon>;
+
+    /// Drawn for conditions the theme doesn't cover and ids OpenWeather doesn't document
+    fn fallback(&self) -> Icon;
+
+    /// Conditions with a glyph of their own
+    fn coverage(&self) -> Vec<WeatherConditionId> {
+        WeatherConditionId::all()
+            .filter(|condition| self.condition_icon(*condition, IconContext::default()).is_some())
+            .collect()
+    }
+
+    /// Conditions drawn with the fallback glyph
+    fn missing(&self) -> Vec<WeatherConditionId> {
+        let coverage = self.coverage();
+        WeatherConditionId::all()
+            .filter(|condition| !coverage.contains(condition))
+            .collect()
+    }
+
+    fn icon(&self, condition: WeatherConditionId, context: IconContext) -> Icon {
+        self.condition_icon(condition, context).unwrap_or_else(|| self.fallback())
+    }
+}
+
+/// Themes that can be selected by name
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Theme {
+    /// Erik Flowers' Weather Icons, with day, night and wind variants
+    WeatherIcons,
+    /// One outline glyph per kind of weather, drawn for this project
+    Minimal,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 2] = [Theme::WeatherIcons, Theme::Minimal];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Theme::WeatherIcons => "weather-icons",
+            Theme::Minimal => "minimal",
+        }
+    }
+
+    pub fn build(&self) -> Box<dyn IconTheme> {
+        match self {
+            Theme::WeatherIcons => Box::new(WeatherIcons::new()),
+            Theme::Minimal => Box::new(Minimal),
+        }
+    }
+}
+
+impl FromStr for Theme {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self> {
+        match Theme::ALL.iter().find(|theme| theme.name() == name) {
+            Some(theme) => Ok(*theme),
+            None => bail!("Unknown icon theme '{}'", name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use super::*;
+    use crate::icons::glyphs::CONDITION_GLYPHS;
+    use crate::owm::model::WeatherCondition;
+
+    fn contexts() -> impl Iterator<Item = IconContext> {
+        (0..8).map(|bits| IconContext {
+            night: bits & 1 != 0,
+            cloudy: bits & 2 != 0,
+            windy: bits & 4 != 0,
+        })
+    }
+
+    #[test]
+    fn every_glyph_of_every_theme_is_embedded() {
+        for theme in Theme::ALL {
+            let theme = theme.build();
+            assert!(CONDITION_GLYPHS.contains(&theme.fallback()), "{} fallback", theme.name());
+            for condition in WeatherConditionId::all() {
+                for context in contexts() {
+                    let icon = theme.icon(condition, context);
+                    assert!(CONDITION_GLYPHS.contains(&icon), "{} draws {:?} with {:?}", theme.name(), condition, icon);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn every_theme_covers_every_condition() {
+        for theme in Theme::ALL {
+            let theme = theme.build();
+            assert_eq!(theme.missing(), [], "{} has no glyph for these conditions", theme.name());
+            assert_eq!(theme.coverage().len(), WeatherConditionId::all().count());
+        }
+    }
+
+    #[test]
+    fn themes_draw_from_their_own_asset_set() {
+        for (theme, prefix) in [(Theme::WeatherIcons, "wi-"), (Theme::Minimal, "outline-")] {
+            let theme = theme.build();
+            assert!(theme.fallback().name().starts_with(prefix));
+            for condition in WeatherConditionId::all() {
+                for context in contexts() {
+                    let icon = theme.icon(condition, context);
+                    assert!(icon.name().starts_with(prefix), "{} draws {:?} with {}", theme.name(), condition, icon.name());
+                }
+            }
+        }
+    }
+
+    /// Condition as OpenWeather reports it, day or night from the icon code
+    fn reported(id: u16, suffix: char) -> WeatherCondition {
+        serde_json::from_value(json!({ "id": id, "main": "", "description": "", "icon": format!("01{}", suffix) })).unwrap()
+    }
+
+    #[test]
+    fn known_ids_never_draw_the_fallback() {
+        for theme in Theme::ALL {
+            let theme = theme.build();
+            for id in WeatherConditionId::all().map(|condition| condition.id()) {
+                for suffix in ['d', 'n'] {
+                    let weather = reported(id, suffix);
+                    let condition = weather.get_condition().unwrap();
+                    let context = IconContext { night: weather.is_night().unwrap(), cloudy: false, windy: false };
+                    let icon = theme.icon(condition, context);
+                    assert_ne!(icon, theme.fallback(), "{} draws {}{} with {}", theme.name(), id, suffix, icon.name());
+                    assert_ne!(icon, Icon::WiNa, "{} draws {}{} with {}", theme.name(), id, suffix, icon.name());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn clouds_map_to_their_glyph() {
+        use WeatherConditionId::*;
+
+        let day = IconContext { night: false, cloudy: false, windy: false };
+        let night = IconContext { night: true, ..day };
+        let cases = [
+            (Theme::WeatherIcons, ClearSky, day, Icon::WiDaySunny),
+            (Theme::WeatherIcons, ClearSky, night, Icon::WiNightClear),
+            (Theme::WeatherIcons, FewClouds, day, Icon::WiDayCloudy),
+            (Theme::WeatherIcons, FewClouds, night, Icon::WiNightAltCloudy),
+            (Theme::WeatherIcons, ScatteredClouds, day, Icon::WiCloud),
+            (Theme::WeatherIcons, BrokenClouds, day, Icon::WiCloudy),
+            (Theme::WeatherIcons, BrokenClouds, night, Icon::WiCloudy),
+            (Theme::WeatherIcons, OvercastClouds, day, Icon::WiCloudy),
+            (Theme::Minimal, ClearSky, night, Icon::OutlineMoon),
+            (Theme::Minimal, BrokenClouds, day, Icon::OutlineCloud),
+            (Theme::Minimal, OvercastClouds, day, Icon::OutlineOvercast),
+        ];
+        for (theme, condition, context, expected) in cases {
+            assert_eq!(theme.build().icon(condition, context), expected, "{} {:?} {:?}", theme.name(), condition, context);
+        }
+    }
+}
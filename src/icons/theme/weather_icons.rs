@@ -0,0 +1,375 @@
+use crate::icons::registry::Icon;
+use crate::icons::theme::{IconContext, IconTheme};
+use crate::owm::model::WeatherConditionId;
+
+/// Erik Flowers' Weather Icons, the `wi-*` files, covering every condition
+pub struct WeatherIcons {
+    set: WeatherIconSet,
+}
+
+impl WeatherIcons {
+    pub fn new() -> Self {
+        WeatherIcons { set: WeatherIconSet::new() }
+    }
+}
+
+impl Default for WeatherIcons {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IconTheme for WeatherIcons {
+    fn name(&self) -> &'static str {
+        "weather-icons"
+    }
+
+    fn condition_icon(&self, condition: WeatherConditionId, context: IconContext) -> Option<Icon> {
+        use WeatherConditionId::*;
+
+        let icon = match condition {
+            // Thunderstorm, 2xx
+            ThunderstormWithLightRain | ThunderstormWithRain | ThunderstormWithHeavyRain
+            | LightThunderstorm | Thunderstorm | HeavyThunderstorm | RaggedThunderstorm
+            | ThunderstormWithLightDrizzle | ThunderstormWithDrizzle | ThunderstormWithHeavyDrizzle => &self.set.thunderstorm,
+            // Drizzle, 3xx
+            LightIntensityDrizzle | Drizzle | HeavyIntensityDrizzle | LightIntensityDrizzleRain
+            | DrizzleRain | HeavyIntensityDrizzleRain | ShowerRainAndDrizzle
+            | HeavyShowerRainAndDrizzle | ShowerDrizzle => &self.set.drizzle,
+            // Rain, 5xx
+            LightRain | ModerateRain | HeavyIntensityRain | VeryHeavyRain | ExtremeRain | FreezingRain
+            | LightIntensityShowerRain | ShowerRain | HeavyIntensityShowerRain | RaggedShowerRain => &self.set.rain,
+            // Snow, 6xx
+            LightSnow | Snow | HeavySnow | LightShowerSnow | ShowerSnow | HeavyShowerSnow => &self.set.snow,
+            Sleet | LightShowerSleet | ShowerSleet | LightRainAndSnow | RainAndSnow => &self.set.rain_mix,
+            // Atmosphere, 7xx
+            Mist | Haze | Fog => &self.set.fog,
+            Smoke => &self.set.smoke,
+            SandOrDustWhirls | Sand => &self.set.sand,
+            Dust => &self.set.dust,
+            VolcanicAsh => &self.set.volcanic,
+            Squalls => &self.set.squalls,
+            Tornado => &self.set.tornado,
+            // Clear and clouds, 800 and 80x
+            ClearSky => &self.set.clear,
+            FewClouds => &self.set.few_clouds,
+            ScatteredClouds => &self.set.scattered,
+            BrokenClouds => &self.set.broken,
+            OvercastClouds => &self.set.overcast,
+        };
+        Some(get_icon(icon, context))
+    }
+
+    fn fallback(&self) -> Icon {
+        Icon::WiNa
+    }
+}
+
+fn get_icon(icon: &WeatherIcon, context: IconContext) -> Icon {
+    let set = if context.cloudy {
+        &icon.cloudy
+    } else if context.night {
+        &icon.night
+    } else {
+        &icon.day
+    };
+    if context.windy {set.windy} else {set.clear}
+}
+
+struct IconSet {
+    windy: Icon,
+    clear: Icon,
+}
+
+struct WeatherIcon {
+    day: IconSet,
+    night: IconSet,
+    cloudy: IconSet,
+}
+
+struct WeatherIconSet {
+    clear: WeatherIcon,
+    few_clouds: WeatherIcon,
+    scattered: WeatherIcon,
+    broken: WeatherIcon,
+    overcast: WeatherIcon,
+    snow: WeatherIcon,
+    rain: WeatherIcon,
+    rain_mix: WeatherIcon,
+    thunderstorm: WeatherIcon,
+    drizzle: WeatherIcon,
+    fog: WeatherIcon,
+    smoke: WeatherIcon,
+    dust: WeatherIcon,
+    sand: WeatherIcon,
+    volcanic: WeatherIcon,
+    squalls: WeatherIcon,
+    tornado: WeatherIcon,
+}
+
+impl WeatherIconSet {
+    fn new() -> Self {
+        let clear = WeatherIcon {
+            day: IconSet {
+                clear: Icon::WiDaySunny,
+                windy: Icon::WiDayWindy,
+            },
+            night: IconSet {
+                clear: Icon::WiNightClear,
+                windy: Icon::WiNightClear,
+            },
+            cloudy: IconSet {
+                clear: Icon::WiCloud,
+                windy: Icon::WiCloudyWindy,
+            }
+        };
+        let few_clouds = WeatherIcon {
+            day: IconSet {
+                clear: Icon::WiDayCloudy,
+                windy: Icon::WiDayCloudyWindy,
+            },
+            night: IconSet {
+                clear: Icon::WiNightAltCloudy,
+                windy: Icon::WiNightAltCloudyWindy,
+            },
+            cloudy: IconSet {
+                clear: Icon::WiCloud,
+                windy: Icon::WiCloudyWindy,
+            }
+        };
+        let scattered = WeatherIcon {
+            day: IconSet {
+                clear: Icon::WiCloud,
+                windy: Icon::WiCloudyWindy,
+            },
+            night: IconSet {
+                clear: Icon::WiCloud,
+                windy: Icon::WiCloudyWindy,
+            },
+            cloudy: IconSet {
+                clear: Icon::WiCloud,
+                windy: Icon::WiCloudyWindy,
+            }
+        };
+        // Weather Icons has nothing between a single cloud and an overcast sky,
+        // mostly covered is closer to overcast
+        let broken = WeatherIcon {
+            day: IconSet {
+                clear: Icon::WiCloudy,
+                windy: Icon::WiCloudyWindy,
+            },
+            night: IconSet {
+                clear: Icon::WiCloudy,
+                windy: Icon::WiCloudyWindy,
+            },
+            cloudy: IconSet {
+                clear: Icon::WiCloudy,
+                windy: Icon::WiCloudyWindy,
+            }
+        };
+        let overcast = WeatherIcon {
+            day: IconSet {
+                clear: Icon::WiCloudy,
+                windy: Icon::WiCloudyWindy,
+            },
+            night: IconSet {
+                clear: Icon::WiCloudy,
+                windy: Icon::WiCloudyWindy,
+            },
+            cloudy: IconSet {
+                clear: Icon::WiCloudy,
+                windy: Icon::WiCloudyWindy,
+            }
+        };
+        let snow = WeatherIcon {
+            day: IconSet {
+                clear: Icon::WiDaySnow,
+                windy: Icon::WiDaySnowWind,
+            },
+            night: IconSet {
+                clear: Icon::WiNightAltSnow,
+                windy: Icon::WiNightAltSnowWind,
+            },
+            cloudy: IconSet {
+                clear: Icon::WiSnow,
+                windy: Icon::WiSnowWind,
+            }
+        };
+        let rain = WeatherIcon {
+            day: IconSet {
+                clear: Icon::WiDayRain,
+                windy: Icon::WiDayRainWind,
+            },
+            night: IconSet {
+                clear: Icon::WiNightAltRain,
+                windy: Icon::WiNightAltRainWind,
+            },
+            cloudy: IconSet {
+                clear: Icon::WiRain,
+                windy: Icon::WiRainWind,
+            }
+        };
+        let rain_mix = WeatherIcon {
+            day: IconSet {
+                clear: Icon::WiDayRainMix,
+                windy: Icon::WiDayRainMix,
+            },
+            night: IconSet {
+                clear: Icon::WiNightAltRainMix,
+                windy: Icon::WiNightAltRainMix,
+            },
+            cloudy: IconSet {
+                clear: Icon::WiRainMix,
+                windy: Icon::WiRainMix,
+            }
+        };
+        let drizzle = WeatherIcon {
+            day: IconSet {
+                clear: Icon::WiDayShowers,
+                windy: Icon::WiDayShowers,
+            },
+            night: IconSet {
+                clear: Icon::WiNightAltShowers,
+                windy: Icon::WiNightAltShowers,
+            },
+            cloudy: IconSet {
+                clear: Icon::WiShowers,
+                windy: Icon::WiShowers,
+            }
+        };
+        let thunderstorm = WeatherIcon {
+            day: IconSet {
+                clear: Icon::WiDayThunderstorm,
+                windy: Icon::WiDayThunderstorm,
+            },
+            night: IconSet {
+                clear: Icon::WiNightAltThunderstorm,
+                windy: Icon::WiNightAltThunderstorm,
+            },
+            cloudy: IconSet {
+                clear: Icon::WiThunderstorm,
+                windy: Icon::WiThunderstorm,
+            }
+        };
+        let fog = WeatherIcon {
+            day: IconSet {
+                clear: Icon::WiDayFog,
+                windy: Icon::WiDayFog,
+            },
+            night: IconSet {
+                clear: Icon::WiNightFog,
+                windy: Icon::WiNightFog,
+            },
+            cloudy: IconSet {
+                clear: Icon::WiFog,
+                windy: Icon::WiFog,
+            }
+        };
+        let smoke = WeatherIcon {
+            day: IconSet {
+                clear: Icon::WiSmoke,
+                windy: Icon::WiSmoke,
+            },
+            night: IconSet {
+                clear: Icon::WiSmoke,
+                windy: Icon::WiSmoke,
+            },
+            cloudy: IconSet {
+                clear: Icon::WiSmoke,
+                windy: Icon::WiSmoke,
+            }
+        };
+        let dust = WeatherIcon {
+            day: IconSet {
+                clear: Icon::WiDust,
+                windy: Icon::WiDust,
+            },
+            night: IconSet {
+                clear: Icon::WiDust,
+                windy: Icon::WiDust,
+            },
+            cloudy: IconSet {
+                clear: Icon::WiDust,
+                windy: Icon::WiDust,
+            }
+        };
+        let sand = WeatherIcon {
+            day: IconSet {
+                clear: Icon::WiSandstorm,
+                windy: Icon::WiSandstorm,
+            },
+            night: IconSet {
+                clear: Icon::WiSandstorm,
+                windy: Icon::WiSandstorm,
+            },
+            cloudy: IconSet {
+                clear: Icon::WiSandstorm,
+                windy: Icon::WiSandstorm,
+            }
+        };
+        let squalls = WeatherIcon {
+            day: IconSet {
+                clear: Icon::WiCloudyGusts,
+                windy: Icon::WiCloudyGusts,
+            },
+            night: IconSet {
+                clear: Icon::WiCloudyGusts,
+                windy: Icon::WiCloudyGusts,
+            },
+            cloudy: IconSet {
+                clear: Icon::WiCloudyGusts,
+                windy: Icon::WiCloudyGusts,
+            }
+        };
+        let tornado = WeatherIcon {
+            day: IconSet {
+                clear: Icon::WiTornado,
+                windy: Icon::WiTornado,
+            },
+            night: IconSet {
+                clear: Icon::WiTornado,
+                windy: Icon::WiTornado,
+            },
+            cloudy: IconSet {
+                clear: Icon::WiTornado,
+                windy: Icon::WiTornado,
+            }
+        };
+        let volcanic = WeatherIcon {
+            day: IconSet {
+                clear: Icon::WiVolcano,
+                windy: Icon::WiVolcano,
+            },
+            night: IconSet {
+                clear: Icon::WiVolcano,
+                windy: Icon::WiVolcano,
+            },
+            cloudy: IconSet {
+                clear: Icon::WiVolcano,
+                windy: Icon::WiVolcano,
+            }
+        };
+
+        Self {
+            clear,
+            few_clouds,
+            scattered,
+            broken,
+            overcast,
+            snow,
+            rain,
+            rain_mix,
+            thunderstorm,
+            drizzle,
+            fog,
+            smoke,
+            dust,
+            sand,
+            volcanic,
+            squalls,
+            tornado,
+        }
+    }
+
+}
+
@@ -0,0 +1,60 @@
+use std::thread;
+use std::time::{Duration, Instant};
+use anyhow::Result;
+use esp_idf_hal::gpio::*;
+use esp_idf_sys::{esp, esp_sleep_enable_ext0_wakeup, esp_sleep_get_wakeup_cause, esp_sleep_source_t_ESP_SLEEP_WAKEUP_EXT0};
+use log::*;
+use crate::input::{Gesture, GestureConfig, GestureDetector};
+
+/// Level polling interval, well below the debounce time
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Active low push button with a pull-up, e.g. the BOOT button of most boards.
+pub struct Button {
+    pin: PinDriver<'static, AnyIOPin, Input>,
+    detector: GestureDetector,
+    started: Instant,
+}
+
+impl Button {
+    /// `pin` must be an RTC GPIO for the button to wake the chip from deep sleep.
+    pub fn new(pin: AnyIOPin, config: GestureConfig) -> Result<Self> {
+        let pin_number = pin.pin();
+        let mut pin = PinDriver::input(pin)?;
+        pin.set_pull(Pull::Up)?;
+
+        // Pressing the button pulls the pin low
+        esp!(unsafe { esp_sleep_enable_ext0_wakeup(pin_number, 0) })?;
+
+        // A button still held, e.g. after waking the chip, is ignored until released
+        let pressed = pin.is_low();
+
+        Ok(Button {
+            pin,
+            detector: GestureDetector::new(config, pressed),
+            started: Instant::now(),
+        })
+    }
+
+    /// Whether the chip was woken from deep sleep by the button
+    pub fn woke_up_device() -> bool {
+        unsafe { esp_sleep_get_wakeup_cause() == esp_sleep_source_t_ESP_SLEEP_WAKEUP_EXT0 }
+    }
+
+    /// Polls the button until a gesture completes or `timeout` elapses.
+    /// Keeps polling past the timeout while a gesture is in progress.
+    pub fn wait(&mut self, timeout: Duration) -> Option<Gesture> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let pressed = self.pin.is_low();
+            if let Some(gesture) = self.detector.update(pressed, self.started.elapsed()) {
+                info!("Button gesture: {:?}", gesture);
+                return Some(gesture);
+            }
+            if Instant::now() >= deadline && !self.detector.is_active() {
+                return None;
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
@@ -0,0 +1,222 @@
+use std::time::Duration;
+
+/// Ways the button can be pressed
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Gesture {
+    Short,
+    Double,
+    Long,
+    /// Held for `GestureConfig::reset_hold`, reported while still held
+    FactoryReset,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct GestureConfig {
+    /// Edges closer than this to the previous one are contact bounce
+    pub debounce: Duration,
+    /// Minimum press length of a long press
+    pub long_press: Duration,
+    /// Maximum time between the release of a short press and the second press
+    pub double_press: Duration,
+    pub reset_hold: Duration,
+}
+
+impl Default for GestureConfig {
+    fn default() -> Self {
+        GestureConfig {
+            debounce: Duration::from_millis(30),
+            long_press: Duration::from_millis(800),
+            double_press: Duration::from_millis(350),
+            reset_hold: Duration::from_secs(10),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum State {
+    Idle,
+    Pressed { since: Duration },
+    /// Short press released, a second press makes it a double press
+    Released { at: Duration },
+    SecondPress,
+    /// Waiting for the button to be released before detecting anything, after
+    /// a factory reset or when it was already held at startup
+    WaitRelease,
+}
+
+/// Turns the raw button level into gestures.
+///
+/// Times are relative to any fixed point, the detector only compares them.
+/// `update` must be called with the level on every poll, edges or not, so
+/// pending gestures are reported once they time out.
+pub struct GestureDetector {
+    config: GestureConfig,
+    state: State,
+    pressed: bool,
+    last_edge: Option<Duration>,
+}
+
+#[allow(dead_code)]
+impl GestureDetector {
+    pub fn new(config: GestureConfig, pressed: bool) -> Self {
+        GestureDetector {
+            config,
+            state: if pressed { State::WaitRelease } else { State::Idle },
+            pressed,
+            last_edge: None,
+        }
+    }
+
+    /// Feeds the button level at `now`, returns the gesture it completes if any
+    pub fn update(&mut self, pressed: bool, now: Duration) -> Option<Gesture> {
+        if pressed != self.pressed {
+            let bouncing = self.last_edge
+                .is_some_and(|last_edge| now.saturating_sub(last_edge) < self.config.debounce);
+            if !bouncing {
+                self.pressed = pressed;
+                self.last_edge = Some(now);
+                if let Some(gesture) = self.edge(pressed, now) {
+                    return Some(gesture);
+                }
+            }
+        }
+
+        self.tick(now)
+    }
+
+    /// Whether a gesture is in progress and the caller should keep polling
+    pub fn is_active(&self) -> bool {
+        matches!(self.state, State::Pressed { .. } | State::Released { .. } | State::SecondPress)
+    }
+
+    fn edge(&mut self, pressed: bool, now: Duration) -> Option<Gesture> {
+        let (state, gesture) = match (self.state, pressed) {
+            (State::Idle, true) => (State::Pressed { since: now }, None),
+            (State::Pressed { since }, false) if now.saturating_sub(since) >= self.config.reset_hold => (State::Idle, Some(Gesture::FactoryReset)),
+            (State::Pressed { since }, false) if now.saturating_sub(since) >= self.config.long_press => (State::Idle, Some(Gesture::Long)),
+            (State::Pressed { .. }, false) => (State::Released { at: now }, None),
+            // Too late for a double press, the pending short press is reported first
+            (State::Released { at }, true) if now.saturating_sub(at) > self.config.double_press => (State::Pressed { since: now }, Some(Gesture::Short)),
+            (State::Released { .. }, true) => (State::SecondPress, None),
+            (State::SecondPress, false) => (State::Idle, Some(Gesture::Double)),
+            (State::WaitRelease, false) => (State::Idle, None),
+            (state, _) => (state, None),
+        };
+        self.state = state;
+        gesture
+    }
+
+    fn tick(&mut self, now: Duration) -> Option<Gesture> {
+        match self.state {
+            State::Pressed { since } if now.saturating_sub(since) >= self.config.reset_hold => {
+                self.state = State::WaitRelease;
+                Some(Gesture::FactoryReset)
+            }
+            State::Released { at } if now.saturating_sub(at) > self.config.double_press => {
+                self.state = State::Idle;
+                Some(Gesture::Short)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Poll interval of the simulated button, in ms
+    const STEP: u64 = 5;
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    /// Polls `detector` every `STEP` until `end`, the level following `edges`
+    /// (time in ms, pressed). Returns the gestures with the time they were reported.
+    fn simulate(detector: &mut GestureDetector, edges: &[(u64, bool)], end: u64) -> Vec<(u64, Gesture)> {
+        let mut pressed = detector.pressed;
+        (0..=end).step_by(STEP as usize)
+            .filter_map(|now| {
+                if let Some((_, level)) = edges.iter().rev().find(|(at, _)| *at <= now) {
+                    pressed = *level;
+                }
+                detector.update(pressed, ms(now)).map(|gesture| (now, gesture))
+            })
+            .collect()
+    }
+
+    fn gestures(edges: &[(u64, bool)], end: u64) -> Vec<(u64, Gesture)> {
+        simulate(&mut GestureDetector::new(GestureConfig::default(), false), edges, end)
+    }
+
+    #[test]
+    fn short_press_is_reported_once_no_second_press_can_follow() {
+        assert_eq!(gestures(&[(100, true), (200, false)], 1000), [(555, Gesture::Short)]);
+    }
+
+    #[test]
+    fn bounces_are_ignored() {
+        let clean = gestures(&[(100, true), (300, false)], 1000);
+        let bouncing = gestures(&[(100, true), (105, false), (110, true), (115, false), (120, true), (300, false), (310, true), (320, false)], 1000);
+        assert_eq!(clean, [(655, Gesture::Short)]);
+        assert_eq!(bouncing, clean);
+    }
+
+    #[test]
+    fn double_press() {
+        assert_eq!(gestures(&[(100, true), (200, false), (400, true), (500, false)], 1500), [(500, Gesture::Double)]);
+    }
+
+    #[test]
+    fn presses_too_far_apart_are_two_short_presses() {
+        assert_eq!(
+            gestures(&[(100, true), (200, false), (600, true), (700, false)], 1500),
+            [(555, Gesture::Short), (1055, Gesture::Short)],
+        );
+    }
+
+    #[test]
+    fn late_second_press_reports_the_pending_short_press_first() {
+        // Not polled in between, e.g. while the display refreshed
+        let mut detector = GestureDetector::new(GestureConfig::default(), false);
+        assert_eq!(detector.update(true, ms(100)), None);
+        assert_eq!(detector.update(false, ms(200)), None);
+        assert_eq!(detector.update(true, ms(900)), Some(Gesture::Short));
+        assert_eq!(detector.update(false, ms(1000)), None);
+        assert_eq!(detector.update(false, ms(1351)), Some(Gesture::Short));
+    }
+
+    #[test]
+    fn long_press_is_reported_on_release() {
+        assert_eq!(gestures(&[(100, true), (895, false)], 2000), [(1250, Gesture::Short)]);
+        assert_eq!(gestures(&[(100, true), (900, false)], 2000), [(900, Gesture::Long)]);
+        assert_eq!(gestures(&[(100, true), (5000, false)], 6000), [(5000, Gesture::Long)]);
+    }
+
+    #[test]
+    fn factory_reset_is_reported_while_held() {
+        let edges = [(100, true), (12_000, false), (13_000, true), (13_100, false)];
+        assert_eq!(gestures(&edges, 14_000), [(10_100, Gesture::FactoryReset), (13_455, Gesture::Short)]);
+    }
+
+    #[test]
+    fn button_held_at_start_is_ignored_until_released() {
+        let mut detector = GestureDetector::new(GestureConfig::default(), true);
+        assert!(!detector.is_active());
+        let edges = [(0, true), (11_000, false), (12_000, true), (12_100, false)];
+        assert_eq!(simulate(&mut detector, &edges, 13_000), [(12_455, Gesture::Short)]);
+    }
+
+    #[test]
+    fn active_while_a_gesture_is_pending() {
+        let mut detector = GestureDetector::new(GestureConfig::default(), false);
+        assert!(!detector.is_active());
+        detector.update(true, ms(100));
+        assert!(detector.is_active());
+        detector.update(false, ms(200));
+        assert!(detector.is_active());
+        assert_eq!(detector.update(false, ms(551)), Some(Gesture::Short));
+        assert!(!detector.is_active());
+    }
+}
@@ -0,0 +1,63 @@
+use std::str::FromStr;
+use anyhow::{bail, Error, Result};
+
+mod gesture;
+pub mod button;
+
+pub use gesture::{Gesture, GestureConfig, GestureDetector};
+
+/// What the station does in response to a gesture
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Action {
+    NextPage,
+    ForceFetch,
+    ShowDiagnostics,
+    /// Switches to the next icon theme, kept across restarts
+    NextIconTheme,
+    FactoryReset,
+    None,
+}
+
+impl FromStr for Action {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self> {
+        Ok(match name {
+            "next_page" => Action::NextPage,
+            "force_fetch" => Action::ForceFetch,
+            "diagnostics" => Action::ShowDiagnostics,
+            "next_theme" => Action::NextIconTheme,
+            "none" | "" => Action::None,
+            _ => bail!("Unknown button action '{}'", name),
+        })
+    }
+}
+
+/// Actions bound to each gesture. Factory reset is always bound to the long hold.
+#[derive(Copy, Clone, Debug)]
+pub struct ButtonMapping {
+    pub short: Action,
+    pub double: Action,
+    pub long: Action,
+}
+
+impl ButtonMapping {
+    pub fn action(&self, gesture: Gesture) -> Action {
+        match gesture {
+            Gesture::Short => self.short,
+            Gesture::Double => self.double,
+            Gesture::Long => self.long,
+            Gesture::FactoryReset => Action::FactoryReset,
+        }
+    }
+}
+
+impl Default for ButtonMapping {
+    fn default() -> Self {
+        ButtonMapping {
+            short: Action::NextPage,
+            double: Action::ForceFetch,
+            long: Action::ShowDiagnostics,
+        }
+    }
+}
@@ -0,0 +1,145 @@
+//! JSON description of a screen layout.
+//!
+//! Every node is an object. Containers have either a `row` or a `column` list
+//! of children, leaves usually have a `widget`:
+//!
+//! ```json
+//! {
+//!   "padding": { "top": 8, "left": 8 },
+//!   "column": [
+//!     { "name": "title", "widget": "date_location", "fixed": 64 },
+//!     { "widget": "daily_forecast", "font": "profont17", "options": { "days": 7 } }
+//!   ]
+//! }
+//! ```
+//!
+//! Nodes are `flex: 1` unless they specify `fixed` or `flex`.
+//! This module is also compiled by `build.rs` to validate layouts on the host,
+//! it must not depend on anything target specific.
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use crate::layout::{Align, Node, Padding};
+
+/// Layouts shipped with the firmware, by name
+pub const PRESETS: &[(&str, &str)] = &[
+    ("overview", include_str!("presets/overview.json")),
+    ("week", include_str!("presets/week.json")),
+    ("minimal", include_str!("presets/minimal.json")),
+    ("hourly", include_str!("presets/hourly.json")),
+    ("daily", include_str!("presets/daily.json")),
+    ("alerts", include_str!("presets/alerts.json")),
+    ("astronomy", include_str!("presets/astronomy.json")),
+    ("comparison", include_str!("presets/comparison.json")),
+];
+
+pub const DEFAULT_PRESET: &str = "overview";
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NodeDescription {
+    name: Option<String>,
+    widget: Option<String>,
+    font: Option<String>,
+    #[serde(default)]
+    options: Map<String, Value>,
+    fixed: Option<u32>,
+    flex: Option<u32>,
+    cross: Option<u32>,
+    align: Option<AlignDescription>,
+    padding: Option<PaddingDescription>,
+    row: Option<Vec<NodeDescription>>,
+    column: Option<Vec<NodeDescription>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum AlignDescription {
+    Start,
+    Center,
+    End,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PaddingDescription {
+    All(u32),
+    Sides {
+        #[serde(default)]
+        top: u32,
+        #[serde(default)]
+        right: u32,
+        #[serde(default)]
+        bottom: u32,
+        #[serde(default)]
+        left: u32,
+    },
+}
+
+pub fn parse(json: &str) -> Result<Node> {
+    let description: NodeDescription = serde_json::from_str(json)?;
+    description.into_node()
+}
+
+pub fn preset(name: &str) -> Result<Node> {
+    match PRESETS.iter().find(|(preset, _)| *preset == name) {
+        Some((_, json)) => parse(json).with_context(|| format!("Invalid preset layout '{}'", name)),
+        None => bail!("No preset layout named '{}'", name),
+    }
+}
+
+impl NodeDescription {
+    fn into_node(self) -> Result<Node> {
+        let label = self.name.clone().or_else(|| self.widget.clone()).unwrap_or_else(|| String::from("unnamed node"));
+
+        let mut node = match (self.row, self.column) {
+            (Some(_), Some(_)) => bail!("{} can't be both a row and a column", label),
+            (Some(children), None) => Node::row(Self::into_nodes(children)?),
+            (None, Some(children)) => Node::column(Self::into_nodes(children)?),
+            (None, None) => Node::leaf(),
+        };
+
+        node = match (self.fixed, self.flex) {
+            (Some(_), Some(_)) => bail!("{} can't be both fixed and flex", label),
+            (Some(length), None) => node.fixed(length),
+            (None, Some(weight)) => node.flex(weight),
+            (None, None) => node,
+        };
+
+        if let Some(name) = self.name {
+            node = node.named(name);
+        }
+        if let Some(widget) = self.widget {
+            node = node.widget(widget);
+        }
+        if let Some(font) = self.font {
+            node = node.font(font);
+        }
+        for (key, value) in self.options {
+            node = node.option(key, value);
+        }
+        if let Some(cross) = self.cross {
+            node = node.cross(cross);
+        }
+        if let Some(align) = self.align {
+            node = node.align(match align {
+                AlignDescription::Start => Align::Start,
+                AlignDescription::Center => Align::Center,
+                AlignDescription::End => Align::End,
+            });
+        }
+        if let Some(padding) = self.padding {
+            node = node.padding(match padding {
+                PaddingDescription::All(value) => Padding::all(value),
+                PaddingDescription::Sides { top, right, bottom, left } => Padding { top, right, bottom, left },
+            });
+        }
+
+        Ok(node)
+    }
+
+    fn into_nodes(children: Vec<NodeDescription>) -> Result<Vec<Node>> {
+        children.into_iter().map(NodeDescription::into_node).collect()
+    }
+}
@@ -0,0 +1,512 @@
+use anyhow::{bail, Result};
+use embedded_graphics::{
+    geometry::*,
+    primitives::Rectangle,
+};
+use serde_json::{Map, Value};
+
+pub mod description;
+
+/// Size of a node along the main axis of its parent.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Length {
+    /// Exact size in pixels
+    Fixed(u32),
+    /// Share of the space left once fixed siblings are placed, weighted by the value
+    Flex(u32),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Row,
+    Column,
+}
+
+/// Position of a node on the cross axis of its parent, when it doesn't stretch.
+#[allow(dead_code)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Align {
+    Start,
+    Center,
+    End,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub struct Padding {
+    pub top: u32,
+    pub right: u32,
+    pub bottom: u32,
+    pub left: u32,
+}
+
+#[allow(dead_code)]
+impl Padding {
+    pub const fn all(value: u32) -> Self {
+        Padding { top: value, right: value, bottom: value, left: value }
+    }
+
+    pub const fn top_left(value: u32) -> Self {
+        Padding { top: value, right: 0, bottom: 0, left: value }
+    }
+}
+
+/// Layout tree node.
+///
+/// Containers place their children one after the other along their direction.
+/// Children stretch across the other axis unless they have a `cross` size.
+#[derive(Clone, Debug)]
+pub struct Node {
+    name: Option<String>,
+    widget: Option<String>,
+    font: Option<String>,
+    options: Map<String, Value>,
+    length: Length,
+    cross: Option<u32>,
+    align: Align,
+    padding: Padding,
+    direction: Direction,
+    children: Vec<Node>,
+}
+
+#[allow(dead_code)]
+impl Node {
+    pub fn leaf() -> Self {
+        Node {
+            name: None,
+            widget: None,
+            font: None,
+            options: Map::new(),
+            length: Length::Flex(1),
+            cross: None,
+            align: Align::Start,
+            padding: Padding::default(),
+            direction: Direction::Row,
+            children: Vec::new(),
+        }
+    }
+
+    pub fn row(children: impl IntoIterator<Item = Node>) -> Self {
+        Node {
+            direction: Direction::Row,
+            children: children.into_iter().collect(),
+            ..Node::leaf()
+        }
+    }
+
+    pub fn column(children: impl IntoIterator<Item = Node>) -> Self {
+        Node {
+            direction: Direction::Column,
+            children: children.into_iter().collect(),
+            ..Node::leaf()
+        }
+    }
+
+    pub fn named(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Name of the widget drawn in this node, see `WidgetRegistry`
+    pub fn widget(mut self, widget: impl Into<String>) -> Self {
+        self.widget = Some(widget.into());
+        self
+    }
+
+    /// Font used by the widget instead of its default one
+    pub fn font(mut self, font: impl Into<String>) -> Self {
+        self.font = Some(font.into());
+        self
+    }
+
+    /// Widget specific setting
+    pub fn option(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.options.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn fixed(mut self, length: u32) -> Self {
+        self.length = Length::Fixed(length);
+        self
+    }
+
+    pub fn flex(mut self, weight: u32) -> Self {
+        self.length = Length::Flex(weight);
+        self
+    }
+
+    pub fn cross(mut self, size: u32) -> Self {
+        self.cross = Some(size);
+        self
+    }
+
+    pub fn align(mut self, align: Align) -> Self {
+        self.align = align;
+        self
+    }
+
+    /// Space kept free inside the node, around its children or, on a leaf,
+    /// around its widget.
+    pub fn padding(mut self, padding: Padding) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Computes the rectangle of every node, the root taking the whole `area`.
+    pub fn compute(&self, area: Rectangle) -> Result<Layout> {
+        let mut layout = Layout {
+            area,
+            regions: Vec::new(),
+        };
+        self.place(area, None, &mut layout)?;
+        Ok(layout)
+    }
+
+    fn place(&self, area: Rectangle, parent: Option<usize>, layout: &mut Layout) -> Result<()> {
+        let inner = self.inner(area)?;
+        let index = layout.regions.len();
+        layout.regions.push(Region {
+            name: self.name.clone(),
+            widget: self.widget.clone(),
+            font: self.font.clone(),
+            options: self.options.clone(),
+            // Leaves are drawn into, their padding is kept out of the region
+            rect: if self.children.is_empty() { inner } else { area },
+            parent,
+        });
+
+        if self.children.is_empty() {
+            return Ok(());
+        }
+
+        let (main, cross) = match self.direction {
+            Direction::Row => (inner.size.width, inner.size.height),
+            Direction::Column => (inner.size.height, inner.size.width),
+        };
+
+        let fixed: u32 = self.children.iter()
+            .filter_map(|child| match child.length {
+                Length::Fixed(length) => Some(length),
+                Length::Flex(_) => None,
+            })
+            .sum();
+        if fixed > main {
+            bail!("{} needs {}px but only has {}px", self.describe(), fixed, main);
+        }
+        let total_weight: u32 = self.children.iter()
+            .filter_map(|child| match child.length {
+                Length::Flex(weight) => Some(weight),
+                Length::Fixed(_) => None,
+            })
+            .sum();
+
+        let remaining = main - fixed;
+        let last_flex = self.children.iter().rposition(|child| matches!(child.length, Length::Flex(_)));
+        let mut distributed = 0;
+        let mut offset = 0;
+
+        for (i, child) in self.children.iter().enumerate() {
+            let length = match child.length {
+                Length::Fixed(length) => length,
+                // Last flexible child takes the rounding leftovers
+                Length::Flex(_) if Some(i) == last_flex => remaining - distributed,
+                Length::Flex(weight) => {
+                    let length = remaining * weight / total_weight.max(1);
+                    distributed += length;
+                    length
+                }
+            };
+
+            let child_cross = child.cross.unwrap_or(cross);
+            if child_cross > cross {
+                bail!("{} needs {}px across but only has {}px", child.describe(), child_cross, cross);
+            }
+            let cross_offset = match child.align {
+                Align::Start => 0,
+                Align::Center => (cross - child_cross) / 2,
+                Align::End => cross - child_cross,
+            };
+
+            let rect = match self.direction {
+                Direction::Row => Rectangle::new(
+                    inner.top_left + Point::new(offset as i32, cross_offset as i32),
+                    Size::new(length, child_cross),
+                ),
+                Direction::Column => Rectangle::new(
+                    inner.top_left + Point::new(cross_offset as i32, offset as i32),
+                    Size::new(child_cross, length),
+                ),
+            };
+            child.place(rect, Some(index), layout)?;
+
+            offset += length;
+        }
+
+        Ok(())
+    }
+
+    fn inner(&self, area: Rectangle) -> Result<Rectangle> {
+        let horizontal = self.padding.left + self.padding.right;
+        let vertical = self.padding.top + self.padding.bottom;
+        if horizontal > area.size.width || vertical > area.size.height {
+            bail!("padding of {} is larger than its area", self.describe());
+        }
+
+        Ok(Rectangle::new(
+            area.top_left + Point::new(self.padding.left as i32, self.padding.top as i32),
+            Size::new(area.size.width - horizontal, area.size.height - vertical),
+        ))
+    }
+
+    fn describe(&self) -> String {
+        match &self.name {
+            Some(name) => format!("'{}'", name),
+            None => String::from("unnamed node"),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Region {
+    name: Option<String>,
+    widget: Option<String>,
+    font: Option<String>,
+    options: Map<String, Value>,
+    rect: Rectangle,
+    parent: Option<usize>,
+}
+
+/// A widget and the settings it was placed with
+#[derive(Copy, Clone, Debug)]
+pub struct Placement<'a> {
+    pub widget: &'a str,
+    pub font: Option<&'a str>,
+    pub options: &'a Map<String, Value>,
+    pub rect: Rectangle,
+}
+
+/// Rectangles computed from a [`Node`] tree.
+#[derive(Clone, Debug)]
+pub struct Layout {
+    area: Rectangle,
+    regions: Vec<Region>,
+}
+
+#[allow(dead_code)]
+impl Layout {
+    pub fn get(&self, name: &str) -> Option<Rectangle> {
+        self.regions.iter()
+            .find(|region| region.name.as_deref() == Some(name))
+            .map(|region| region.rect)
+    }
+
+    pub fn region(&self, name: &str) -> Result<Rectangle> {
+        match self.get(name) {
+            Some(rect) => Ok(rect),
+            None => bail!("layout has no region named '{}'", name),
+        }
+    }
+
+    /// Named regions in tree order
+    pub fn regions(&self) -> impl Iterator<Item = (&str, Rectangle)> {
+        self.regions.iter()
+            .filter_map(|region| region.name.as_deref().map(|name| (name, region.rect)))
+    }
+
+    /// Widgets placed in the layout, in drawing order
+    pub fn widgets(&self) -> impl Iterator<Item = Placement<'_>> {
+        self.regions.iter()
+            .filter_map(|region| region.widget.as_deref().map(|widget| Placement {
+                widget,
+                font: region.font.as_deref(),
+                options: &region.options,
+                rect: region.rect,
+            }))
+    }
+
+    /// Checks that every region stays inside the layout area and that no two
+    /// regions overlap, unless one contains the other.
+    pub fn validate(&self) -> Result<()> {
+        for (i, region) in self.regions.iter().enumerate() {
+            if !contains(&self.area, &region.rect) {
+                bail!("region {} escapes the layout area: {:?}", self.describe(i), region.rect);
+            }
+
+            for j in (i + 1)..self.regions.len() {
+                if self.is_ancestor(i, j) {
+                    continue;
+                }
+                let other = &self.regions[j];
+                if !region.rect.intersection(&other.rect).is_zero_sized() {
+                    bail!("regions {} and {} overlap", self.describe(i), self.describe(j));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn is_ancestor(&self, ancestor: usize, mut node: usize) -> bool {
+        while let Some(parent) = self.regions[node].parent {
+            if parent == ancestor {
+                return true;
+            }
+            node = parent;
+        }
+        false
+    }
+
+    fn describe(&self, index: usize) -> String {
+        match &self.regions[index].name {
+            Some(name) => format!("'{}'", name),
+            None => format!("#{}", index),
+        }
+    }
+}
+
+fn contains(outer: &Rectangle, inner: &Rectangle) -> bool {
+    match inner.bottom_right() {
+        Some(bottom_right) => outer.contains(inner.top_left) && outer.contains(bottom_right),
+        // Empty regions only need to start inside the area
+        None => {
+            let end = outer.top_left + outer.size;
+            inner.top_left.x >= outer.top_left.x && inner.top_left.y >= outer.top_left.y
+                && inner.top_left.x <= end.x && inner.top_left.y <= end.y
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+    use super::*;
+
+    fn length() -> impl Strategy<Value = Length> {
+        prop_oneof![
+            (0..80_u32).prop_map(Length::Fixed),
+            (0..5_u32).prop_map(Length::Flex),
+        ]
+    }
+
+    fn align() -> impl Strategy<Value = Align> {
+        prop_oneof![Just(Align::Start), Just(Align::Center), Just(Align::End)]
+    }
+
+    fn padding() -> impl Strategy<Value = Padding> {
+        (0..12_u32, 0..12_u32, 0..12_u32, 0..12_u32)
+            .prop_map(|(top, right, bottom, left)| Padding { top, right, bottom, left })
+    }
+
+    fn leaf() -> impl Strategy<Value = Node> {
+        (length(), proptest::option::of(0..120_u32), align(), padding()).prop_map(|(length, cross, align, padding)| {
+            let node = Node::leaf().align(align).padding(padding);
+            let node = match length {
+                Length::Fixed(length) => node.fixed(length),
+                Length::Flex(weight) => node.flex(weight),
+            };
+            match cross {
+                Some(cross) => node.cross(cross),
+                None => node,
+            }
+        })
+    }
+
+    fn tree() -> impl Strategy<Value = Node> {
+        leaf().prop_recursive(4, 48, 5, |inner| {
+            (leaf(), any::<bool>(), proptest::collection::vec(inner, 1..5)).prop_map(|(node, row, children)| Node {
+                direction: if row { Direction::Row } else { Direction::Column },
+                children,
+                ..node
+            })
+        })
+    }
+
+    fn area() -> impl Strategy<Value = Rectangle> {
+        (-50..50_i32, -50..50_i32, 0..400_u32, 0..300_u32)
+            .prop_map(|(x, y, width, height)| Rectangle::new(Point::new(x, y), Size::new(width, height)))
+    }
+
+    fn count(node: &Node) -> usize {
+        1 + node.children.iter().map(count).sum::<usize>()
+    }
+
+    proptest! {
+        #[test]
+        fn computed_layouts_are_valid(node in tree(), area in area()) {
+            match node.compute(area) {
+                Ok(layout) => {
+                    prop_assert_eq!(layout.regions.len(), count(&node));
+                    let root = if node.children.is_empty() { node.inner(area).unwrap() } else { area };
+                    prop_assert_eq!(layout.regions[0].rect, root);
+                    if let Err(err) = layout.validate() {
+                        return Err(TestCaseError::fail(format!("{:#}", err)));
+                    }
+                }
+                // Trees that don't fit are rejected, never placed outside their area
+                Err(err) => {
+                    let message = err.to_string();
+                    prop_assert!(message.contains("needs") || message.contains("padding"), "{}", message);
+                }
+            }
+        }
+
+        #[test]
+        fn flex_children_fill_their_container(weights in proptest::collection::vec(1..5_u32, 1..6), fixed in 0..100_u32, width in 100..400_u32) {
+            let children = weights.iter().map(|weight| Node::leaf().flex(*weight)).chain([Node::leaf().fixed(fixed)]);
+            let layout = Node::row(children).compute(Rectangle::new(Point::zero(), Size::new(width, 10))).unwrap();
+
+            let total: u32 = layout.regions[1..].iter().map(|region| region.rect.size.width).sum();
+            prop_assert_eq!(total, width);
+            layout.validate().unwrap();
+        }
+
+        #[test]
+        fn fixed_overflow_is_rejected(lengths in proptest::collection::vec(0..100_u32, 1..6), vertical in any::<bool>()) {
+            let total: u32 = lengths.iter().sum();
+            prop_assume!(total > 0);
+            let children = lengths.iter().map(|length| Node::leaf().fixed(*length));
+            let node = if vertical { Node::column(children) } else { Node::row(children) }.named("strip");
+
+            let area = Rectangle::new(Point::zero(), Size::new(total - 1, total - 1));
+            let err = node.compute(area).unwrap_err();
+            prop_assert_eq!(err.to_string(), format!("'strip' needs {}px but only has {}px", total, total - 1));
+
+            let area = Rectangle::new(Point::zero(), Size::new(total, total));
+            prop_assert!(node.compute(area).is_ok());
+        }
+
+        #[test]
+        fn padding_overflow_is_rejected(padding in padding(), width in 0..30_u32, height in 0..30_u32) {
+            let node = Node::column([Node::leaf()]).named("padded").padding(padding);
+            let area = Rectangle::new(Point::zero(), Size::new(width, height));
+            let fits = padding.left + padding.right <= width && padding.top + padding.bottom <= height;
+
+            match node.compute(area) {
+                Ok(layout) => {
+                    prop_assert!(fits);
+                    layout.validate().unwrap();
+                }
+                Err(err) => {
+                    prop_assert!(!fits);
+                    prop_assert_eq!(err.to_string(), "padding of 'padded' is larger than its area");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn leaf_padding_shrinks_its_region() {
+        let node = Node::row([
+            Node::leaf().named("padded").widget("clock").fixed(100).padding(Padding { top: 1, right: 2, bottom: 3, left: 4 }),
+            Node::leaf().named("plain"),
+        ]);
+        let layout = node.compute(Rectangle::new(Point::new(10, 20), Size::new(300, 50))).unwrap();
+
+        let padded = Rectangle::new(Point::new(14, 21), Size::new(94, 46));
+        assert_eq!(layout.region("padded").unwrap(), padded);
+        assert_eq!(layout.widgets().next().unwrap().rect, padded);
+        assert_eq!(layout.region("plain").unwrap(), Rectangle::new(Point::new(110, 20), Size::new(200, 50)));
+        layout.validate().unwrap();
+
+        let err = Node::leaf().named("tight").padding(Padding::all(6)).compute(Rectangle::new(Point::zero(), Size::new(10, 40))).unwrap_err();
+        assert_eq!(err.to_string(), "padding of 'tight' is larger than its area");
+    }
+}
@@ -0,0 +1,298 @@
+use std::cmp::Reverse;
+use std::collections::VecDeque;
+use std::time::Duration;
+use anyhow::{bail, Context, Result};
+
+/// Length of the window the API budget applies to
+const BUDGET_WINDOW: Duration = Duration::from_secs(24 * 60 * 60);
+/// Wait before retrying a failed fetch, doubled after each consecutive failure
+const RETRY_DELAY: Duration = Duration::from_secs(30);
+/// Shortest time between two fetches a location may ask for
+pub const MIN_FETCH_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Location {
+    pub name: String,
+    pub latitude: f32,
+    pub longitude: f32,
+    /// Time between two fetches of this location
+    pub fetch_interval: Duration,
+}
+
+/// Parses a `;` separated list of `name@latitude,longitude`, optionally
+/// followed by `@seconds` between fetches, at least `MIN_FETCH_INTERVAL`, e.g.
+/// `Home@45.50,-73.56;Cottage@46.05,-74.28@1800`.
+pub fn parse_locations(list: &str, default_interval: Duration) -> Result<Vec<Location>> {
+    list.split(';')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| parse_location(entry, default_interval).with_context(|| format!("Invalid location '{}'", entry)))
+        .collect()
+}
+
+fn parse_location(entry: &str, default_interval: Duration) -> Result<Location> {
+    let mut parts = entry.split('@').map(str::trim);
+    let (Some(name), Some(coordinates)) = (parts.next(), parts.next()) else {
+        bail!("expected name@latitude,longitude");
+    };
+    let Some((latitude, longitude)) = coordinates.split_once(',') else {
+        bail!("expected latitude,longitude");
+    };
+    let fetch_interval = match parts.next().map(str::parse).transpose()?.map(Duration::from_secs) {
+        Some(interval) if interval < MIN_FETCH_INTERVAL => {
+            bail!("expected at least {} seconds between fetches", MIN_FETCH_INTERVAL.as_secs())
+        }
+        Some(interval) => interval,
+        None => default_interval,
+    };
+    if parts.next().is_some() || name.is_empty() {
+        bail!("expected name@latitude,longitude[@seconds]");
+    }
+
+    Ok(Location {
+        name: name.to_string(),
+        latitude: latitude.trim().parse()?,
+        longitude: longitude.trim().parse()?,
+        fetch_interval,
+    })
+}
+
+/// Decides which location to fetch next, each on its own interval, without
+/// exceeding a number of API calls per day shared by all of them.
+/// Intervals asking for more calls than the budget allows are stretched by the
+/// same factor, so fetching carries on all day instead of stopping once the
+/// budget is spent. Failed fetches are retried sooner, backing off up to the
+/// normal interval, the budget is then enforced as a hard cap.
+///
+/// Times are relative to any fixed point, see `pages::Clock`.
+pub struct FetchScheduler {
+    intervals: Vec<Duration>,
+    last_fetch: Vec<Option<Duration>>,
+    /// Consecutive failed fetches of each location
+    failures: Vec<u32>,
+    daily_budget: usize,
+    calls: VecDeque<Duration>,
+}
+
+#[allow(dead_code)]
+impl FetchScheduler {
+    pub fn new(locations: &[Location], daily_budget: u32) -> Self {
+        let intervals: Vec<Duration> = locations.iter().map(|location| location.fetch_interval).collect();
+        FetchScheduler {
+            intervals: fit_budget(&intervals, daily_budget),
+            last_fetch: vec![None; locations.len()],
+            failures: vec![0; locations.len()],
+            daily_budget: daily_budget as usize,
+            calls: VecDeque::new(),
+        }
+    }
+
+    /// Location to fetch now, the one waiting the longest first.
+    /// `None` when nothing is due or the budget is spent.
+    pub fn due(&mut self, now: Duration) -> Option<usize> {
+        self.forget_old_calls(now);
+        if self.calls.len() >= self.daily_budget {
+            return None;
+        }
+
+        (0..self.intervals.len())
+            .filter_map(|index| self.overdue_by(index, now).map(|overdue| (index, overdue)))
+            // First location wins ties
+            .min_by_key(|(_, overdue)| Reverse(*overdue))
+            .map(|(index, _)| index)
+    }
+
+    /// Records a call to the API for `index` made at `now`, both successful
+    /// and failed ones count towards the budget
+    pub fn fetched(&mut self, index: usize, now: Duration, success: bool) {
+        self.last_fetch[index] = Some(now);
+        self.calls.push_back(now);
+        self.failures[index] = match success {
+            true => 0,
+            false => self.failures[index].saturating_add(1),
+        };
+    }
+
+    /// Makes every location due, the budget still applies
+    pub fn expire_all(&mut self) {
+        self.last_fetch.iter_mut().for_each(|last| *last = None);
+    }
+
+    /// Time until `due` may return a location
+    pub fn next_due(&mut self, now: Duration) -> Duration {
+        self.forget_old_calls(now);
+        let budget_available = match self.calls.len() >= self.daily_budget {
+            // Budget frees up as the oldest call leaves the window
+            true => self.calls.front().map_or(Duration::ZERO, |oldest| (*oldest + BUDGET_WINDOW).saturating_sub(now)),
+            false => Duration::ZERO,
+        };
+        let next_location = (0..self.intervals.len())
+            .map(|index| match self.last_fetch[index] {
+                Some(last) => (last + self.interval(index)).saturating_sub(now),
+                None => Duration::ZERO,
+            })
+            .min()
+            .unwrap_or(Duration::MAX);
+
+        next_location.max(budget_available)
+    }
+
+    /// API calls left in the current window
+    pub fn remaining_budget(&mut self, now: Duration) -> usize {
+        self.forget_old_calls(now);
+        self.daily_budget.saturating_sub(self.calls.len())
+    }
+
+    fn overdue_by(&self, index: usize, now: Duration) -> Option<Duration> {
+        match self.last_fetch[index] {
+            // Never fetched goes before anything else
+            None => Some(Duration::MAX),
+            Some(last) if now >= last + self.interval(index) => Some(now - (last + self.interval(index))),
+            Some(_) => None,
+        }
+    }
+
+    /// Time between the last fetch of `index` and the next one
+    fn interval(&self, index: usize) -> Duration {
+        match self.failures[index] {
+            0 => self.intervals[index],
+            failures => RETRY_DELAY
+                .saturating_mul(2_u32.saturating_pow(failures - 1))
+                .min(self.intervals[index]),
+        }
+    }
+
+    fn forget_old_calls(&mut self, now: Duration) {
+        while self.calls.front().is_some_and(|call| now.saturating_sub(*call) >= BUDGET_WINDOW) {
+            self.calls.pop_front();
+        }
+    }
+}
+
+/// `intervals` stretched so that together they make at most `daily_budget`
+/// calls per `BUDGET_WINDOW`, rounded up to the second
+fn fit_budget(intervals: &[Duration], daily_budget: u32) -> Vec<Duration> {
+    let calls: f64 = intervals.iter()
+        .map(|interval| BUDGET_WINDOW.as_secs_f64() / interval.as_secs_f64().max(1.0))
+        .sum();
+    let stretch = calls / daily_budget.max(1) as f64;
+    if stretch <= 1.0 {
+        return intervals.to_vec();
+    }
+
+    intervals.iter()
+        .map(|interval| Duration::from_secs((interval.as_secs_f64().max(1.0) * stretch).ceil() as u64))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    fn location(name: &str, fetch_interval: u64) -> Location {
+        Location { name: name.to_string(), latitude: 0.0, longitude: 0.0, fetch_interval: secs(fetch_interval) }
+    }
+
+    #[test]
+    fn parses_locations() {
+        let locations = parse_locations("Home@45.50,-73.56; Cottage @ 46.05, -74.28 @1800;", secs(600)).unwrap();
+        assert_eq!(locations, [
+            Location { name: "Home".to_string(), latitude: 45.50, longitude: -73.56, fetch_interval: secs(600) },
+            Location { name: "Cottage".to_string(), latitude: 46.05, longitude: -74.28, fetch_interval: secs(1800) },
+        ]);
+        assert!(parse_locations("Home", secs(600)).is_err());
+        assert!(parse_locations("@45.5,-73.5", secs(600)).is_err());
+        assert!(parse_locations("Home@45.5,-73.5@60@1", secs(600)).is_err());
+    }
+
+    #[test]
+    fn rejects_intervals_below_the_minimum() {
+        for interval in ["0", "1", "59"] {
+            let entry = format!("Home@45.5,-73.5@{}", interval);
+            assert!(parse_locations(&entry, secs(600)).is_err(), "{}", entry);
+        }
+        let locations = parse_locations("Home@45.5,-73.5@60", secs(600)).unwrap();
+        assert_eq!(locations[0].fetch_interval, MIN_FETCH_INTERVAL);
+    }
+
+    #[test]
+    fn fetches_each_location_on_its_interval() {
+        let mut scheduler = FetchScheduler::new(&[location("a", 600), location("b", 1800)], 1000);
+        assert_eq!(scheduler.due(secs(0)), Some(0));
+        scheduler.fetched(0, secs(0), true);
+        assert_eq!(scheduler.due(secs(0)), Some(1));
+        scheduler.fetched(1, secs(0), true);
+        assert_eq!(scheduler.due(secs(599)), None);
+        assert_eq!(scheduler.next_due(secs(599)), secs(1));
+        assert_eq!(scheduler.due(secs(600)), Some(0));
+    }
+
+    /// Times of the calls made over `days` of successful fetches
+    fn simulate(scheduler: &mut FetchScheduler, days: u32) -> Vec<Duration> {
+        let mut calls = Vec::new();
+        let mut now = secs(0);
+        while now < BUDGET_WINDOW * days {
+            while let Some(index) = scheduler.due(now) {
+                scheduler.fetched(index, now, true);
+                calls.push(now);
+            }
+            now += scheduler.next_due(now);
+        }
+        calls
+    }
+
+    #[test]
+    fn default_config_fetches_all_day_within_budget() {
+        // `fetch_interval` and `api_daily_budget` defaults of src/config.rs
+        let mut scheduler = FetchScheduler::new(&[location("home", 60)], 1000);
+        let calls = simulate(&mut scheduler, 3);
+
+        let longest_gap = calls.windows(2).map(|pair| pair[1] - pair[0]).max().unwrap();
+        assert_eq!(longest_gap, secs(87));
+        for (index, call) in calls.iter().enumerate() {
+            let in_window = calls[index..].iter().take_while(|later| **later - *call < BUDGET_WINDOW).count();
+            assert!(in_window <= 1000, "{} calls in the day from {:?}", in_window, call);
+        }
+    }
+
+    #[test]
+    fn intervals_within_budget_are_kept() {
+        assert_eq!(fit_budget(&[secs(600), secs(1800)], 1000), [secs(600), secs(1800)]);
+        assert_eq!(fit_budget(&[secs(86)], 1000), [secs(87)]);
+        // 2880 calls asked for, every location gets the same share of the stretch
+        assert_eq!(fit_budget(&[secs(60), secs(60)], 1000), [secs(173), secs(173)]);
+        assert_eq!(fit_budget(&[secs(60), secs(120)], 1080), [secs(120), secs(240)]);
+    }
+
+    #[test]
+    fn failed_fetches_back_off() {
+        let mut scheduler = FetchScheduler::new(&[location("a", 600)], 1000);
+        let mut now = secs(0);
+        for delay in [30, 60, 120, 240, 480, 600, 600] {
+            assert_eq!(scheduler.due(now), Some(0));
+            scheduler.fetched(0, now, false);
+            assert_eq!(scheduler.next_due(now), secs(delay));
+            now += secs(delay);
+        }
+
+        scheduler.fetched(0, now, true);
+        assert_eq!(scheduler.next_due(now), secs(600));
+        scheduler.fetched(0, now + secs(600), false);
+        assert_eq!(scheduler.next_due(now + secs(600)), secs(30));
+    }
+
+    #[test]
+    fn failed_fetches_count_towards_the_budget() {
+        let mut scheduler = FetchScheduler::new(&[location("a", 60)], 2);
+        scheduler.fetched(0, secs(0), false);
+        scheduler.fetched(0, secs(30), false);
+        assert_eq!(scheduler.remaining_budget(secs(90)), 0);
+        assert_eq!(scheduler.due(secs(90)), None);
+        assert_eq!(scheduler.next_due(secs(90)), BUDGET_WINDOW - secs(90));
+        assert_eq!(scheduler.due(BUDGET_WINDOW), Some(0));
+    }
+}
@@ -5,24 +5,42 @@ mod config;
 mod weather_station;
 mod http_client;
 mod icons;
+mod layout;
+mod widgets;
+mod storage;
+mod pages;
+mod input;
+mod astronomy;
+mod wind;
+mod text;
+mod locations;
+mod ota;
 
 use esp_idf_sys as _; // If using the `binstart` feature of `esp-idf-sys`, always keep this module imported
 
+use std::thread;
 use std::time::Duration;
 use anyhow::Result;
-use esp_idf_hal::{spi, gpio::*, peripherals::Peripherals, prelude::*, spi::{Dma, SpiDriverConfig, SpiConfig}};
+use log::{error, info, warn};
+use esp_idf_hal::{spi, gpio::*, peripherals::Peripherals, prelude::*, spi::{Dma, SpiDriverConfig, SpiConfig, config::Duplex}};
 use esp_idf_svc::{
     eventloop::EspSystemEventLoop,
 };
 use esp_idf_svc::nvs::EspDefaultNvsPartition;
+use esp_idf_svc::wifi::EspWifi;
 use display::{
     display::Display,
-    display_driver::{DisplayDriver, DisplayDriverConfig, DisplayPins}
+    display_driver::{DisplayDriver, DisplayDriverConfig, DisplayPins, ReadMode}
 };
 use crate::config::CONFIG;
+use crate::input::{GestureConfig, button::Button};
 use crate::display::display::{ DisplayConfig};
+use crate::storage::Storage;
 use crate::weather_station::WeatherStation;
-use crate::wifi::wifi;
+use crate::wifi::{connect, wifi};
+
+/// Wait between attempts to join the network after it failed at boot
+const WIFI_RETRY_DELAY: Duration = Duration::from_secs(30);
 
 
 fn main() -> Result<()> {
@@ -32,24 +50,67 @@ fn main() -> Result<()> {
     // Bind the log crate to the ESP Logging facilities
     esp_idf_svc::log::EspLogger::initialize_default();
 
-    let app_config = CONFIG;
-
     let peripherals = Peripherals::take().unwrap();
     let sysloop = EspSystemEventLoop::take()?;
+
+    // A new firmware is judged on booting: NVS, display, WiFi driver and settings
+    // must come up. The network isn't part of it, an image mustn't be rolled back
+    // because the router is down. Later failures don't roll it back either.
+    let (mut wifi, mut weather_station, mut online) = match boot(peripherals, sysloop.clone()) {
+        Ok(booted) => booted,
+        Err(err) if ota::is_pending_verify() => {
+            error!("Firmware failed to boot: {:#}", err);
+            ota::rollback()?;
+            return Err(err);
+        }
+        Err(err) => return Err(err),
+    };
+    if ota::is_pending_verify() {
+        ota::mark_valid()?;
+    }
+
+    while !online {
+        thread::sleep(WIFI_RETRY_DELAY);
+        online = join_network(&mut wifi, sysloop.clone());
+    }
+
+    weather_station.run()
+}
+
+/// Connects to the configured network, logs why it failed otherwise
+fn join_network(wifi: &mut EspWifi<'static>, sysloop: EspSystemEventLoop) -> bool {
+    let app_config = CONFIG;
+    match connect(wifi, app_config.wifi_ssid, app_config.wifi_psk, sysloop) {
+        Ok(()) => true,
+        Err(err) => {
+            warn!("Joining {} failed: {:#}", app_config.wifi_ssid, err);
+            false
+        }
+    }
+}
+
+/// Brings up the display, the station and the WiFi driver, and tries to join the
+/// network once. Whether it joined is returned, the station starts either way
+/// with what NVS has, e.g. the layout downloaded before.
+fn boot(peripherals: Peripherals, sysloop: EspSystemEventLoop) -> Result<(Box<EspWifi<'static>>, WeatherStation, bool)> {
+    let app_config = CONFIG;
+
     let nvs = EspDefaultNvsPartition::take()?;
+    let storage = Storage::new(nvs.clone())?;
 
-    let _wifi = wifi(
+    let mut wifi = wifi(
         app_config.wifi_ssid,
-        app_config.wifi_psk,
         peripherals.modem,
-        sysloop,
+        sysloop.clone(),
         Some(nvs)
     )?;
-
+    let online = join_network(&mut wifi, sysloop);
 
     let pins = peripherals.pins;
     let spi = peripherals.spi2;
     let sclk = pins.gpio19;
+    // The panel DIN line is bidirectional, reads happen over the same pin (3-wire SPI).
+    // For 4-wire, pass the SDI pin below, drop the duplex setting and use `ReadMode::FourWire`.
     let sdo = pins.gpio23;
 
     let cs = pins.gpio17;
@@ -67,6 +128,7 @@ fn main() -> Result<()> {
             .dma(Dma::Disabled),
         &SpiConfig::new()
             .baudrate(8.MHz().into())
+            .duplex(Duplex::Half3Wire)
     ).unwrap();
 
     let display_driver = DisplayDriver::new(spi_driver, DisplayPins {
@@ -75,16 +137,22 @@ fn main() -> Result<()> {
         dc: PinDriver::output(dc.downgrade_output())?,
         rst: PinDriver::output(rst.downgrade_output())?,
     }, DisplayDriverConfig {
-        delay: Duration::from_micros(200)
+        delay: Duration::from_micros(200),
+        busy_timeout: Duration::from_secs(10),
+        refresh_timeout: Duration::from_secs(30),
+        read_mode: ReadMode::ThreeWire,
     });
 
     let display = Display::new(display_driver, DisplayConfig {
         allow_out_of_bounds_drawing: true
     })?;
 
-    let mut weather_station = WeatherStation::new(display);
-    weather_station.run()?;
-
+    // BOOT button, GPIO0 is an RTC pin so it can also wake the chip up
+    let button = Button::new(pins.gpio0.downgrade(), GestureConfig::default())?;
+    if Button::woke_up_device() {
+        info!("Woken up by button");
+    }
 
-    Ok(())
+    let weather_station = WeatherStation::new(display, storage, button)?;
+    Ok((wifi, weather_station, online))
 }
@@ -0,0 +1,228 @@
+//! Description of the latest firmware, published next to the image:
+//!
+//! ```json
+//! {
+//!   "version": "0.2.0",
+//!   "url": "https://example.com/weather-station-0.2.0.bin",
+//!   "sha256": "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08"
+//! }
+//! ```
+//!
+//! Kept free of anything target specific so it can be exercised on the host.
+
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    pub version: Version,
+    /// HTTPS URL of the application image
+    pub url: String,
+    /// Hex encoded SHA-256 of the image. Only detects corruption, see `ota`.
+    pub sha256: String,
+}
+
+impl Manifest {
+    pub fn parse(json: &str) -> Result<Self> {
+        let manifest: Manifest = serde_json::from_str(json).context("Invalid firmware manifest")?;
+        if !manifest.url.starts_with("https://") {
+            bail!("Firmware URL must use HTTPS: {}", manifest.url);
+        }
+        parse_sha256(&manifest.sha256)?;
+        Ok(manifest)
+    }
+
+    /// Expected digest of the image
+    pub fn digest(&self) -> Result<[u8; 32]> {
+        parse_sha256(&self.sha256)
+    }
+}
+
+/// Manifest at `url` if it describes a firmware newer than `current`.
+/// `fetch` returns the body of a GET request.
+pub fn check_with(url: &str, current: Version, fetch: impl FnOnce(&str) -> Result<String>) -> Result<Option<Manifest>> {
+    let manifest = Manifest::parse(&fetch(url)?)?;
+    Ok(manifest.version.is_newer_than(&current).then_some(manifest))
+}
+
+/// `major.minor.patch` version, pre-release and build suffixes are not supported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl Version {
+    /// Version of the running firmware
+    pub fn current() -> Self {
+        env!("CARGO_PKG_VERSION").parse().expect("Invalid package version")
+    }
+
+    pub fn is_newer_than(&self, other: &Version) -> bool {
+        self.cmp(other) == Ordering::Greater
+    }
+}
+
+impl FromStr for Version {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let parse = || -> Result<Version> {
+            let mut parts = s.trim().trim_start_matches('v').split('.').map(str::parse::<u32>);
+            let (Some(major), Some(minor), Some(patch), None) = (parts.next(), parts.next(), parts.next(), parts.next()) else {
+                bail!("expected major.minor.patch");
+            };
+            Ok(Version { major: major?, minor: minor?, patch: patch? })
+        };
+        parse().with_context(|| format!("Invalid version '{}'", s))
+    }
+}
+
+impl Display for Version {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl<'de> Deserialize<'de> for Version {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let version = String::deserialize(deserializer)?;
+        version.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+fn parse_sha256(hex: &str) -> Result<[u8; 32]> {
+    let hex = hex.trim();
+    if hex.len() != 64 || !hex.is_ascii() {
+        bail!("Invalid SHA-256 '{}', expected 64 hex digits", hex);
+    }
+    let mut digest = [0_u8; 32];
+    for (index, byte) in digest.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[2 * index..2 * index + 2], 16)
+            .with_context(|| format!("Invalid SHA-256 '{}'", hex))?;
+    }
+    Ok(digest)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+    use super::*;
+
+    const SHA256: &str = "9f86d081884c7d659a2feaa0c55ad015a3bf4f1b2b0b822cd15d6c15b0f00a08";
+
+    fn manifest(version: &str, url: &str, sha256: &str) -> String {
+        format!(r#"{{ "version": "{}", "url": "{}", "sha256": "{}" }}"#, version, url, sha256)
+    }
+
+    fn version(major: u32, minor: u32, patch: u32) -> Version {
+        Version { major, minor, patch }
+    }
+
+    /// Answers a single request on a local port with `status` and `body`, returns its URL
+    fn serve(status: u16, body: String) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+            while request.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+            write!(stream, "HTTP/1.1 {} Status\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", status, body.len(), body).unwrap();
+        });
+        format!("http://{}/firmware.json", address)
+    }
+
+    /// Plain HTTP GET standing in for `HttpClient`, which only runs on the device
+    fn get(url: &str) -> Result<String> {
+        let (host, path) = url.trim_start_matches("http://").split_once('/').context("Invalid URL")?;
+        let mut stream = std::net::TcpStream::connect(host)?;
+        write!(stream, "GET /{} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n", path, host)?;
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response)?;
+        let (head, body) = response.split_once("\r\n\r\n").context("Truncated response")?;
+        let status = head.split(' ').nth(1).context("No status")?;
+        if status != "200" {
+            bail!("HTTP {}", status);
+        }
+        Ok(body.to_string())
+    }
+
+    fn check(status: u16, body: String, current: Version) -> Result<Option<Manifest>> {
+        check_with(&serve(status, body), current, get)
+    }
+
+    #[test]
+    fn newer_firmware_is_offered() {
+        let body = manifest("0.3.0", "https://example.com/weather-station-0.3.0.bin", SHA256);
+        let manifest = check(200, body, version(0, 2, 9)).unwrap().unwrap();
+        assert_eq!(manifest.version, version(0, 3, 0));
+        assert_eq!(manifest.url, "https://example.com/weather-station-0.3.0.bin");
+        assert_eq!(manifest.digest().unwrap()[..4], [0x9f, 0x86, 0xd0, 0x81]);
+    }
+
+    #[test]
+    fn same_or_older_firmware_is_ignored() {
+        let body = || manifest("0.3.0", "https://example.com/fw.bin", SHA256);
+        assert_eq!(check(200, body(), version(0, 3, 0)).unwrap(), None);
+        assert_eq!(check(200, body(), version(0, 10, 0)).unwrap(), None);
+        assert_eq!(check(200, body(), version(1, 0, 0)).unwrap(), None);
+    }
+
+    #[test]
+    fn server_errors_are_reported() {
+        let err = check(404, String::from("Not found"), version(0, 1, 0)).unwrap_err();
+        assert_eq!(err.to_string(), "HTTP 404");
+    }
+
+    #[test]
+    fn invalid_manifests_are_rejected() {
+        let current = version(0, 1, 0);
+        let err = check(200, String::from("<html>"), current).unwrap_err();
+        assert_eq!(err.to_string(), "Invalid firmware manifest");
+
+        let err = check(200, manifest("0.2.0", "http://example.com/fw.bin", SHA256), current).unwrap_err();
+        assert_eq!(err.to_string(), "Firmware URL must use HTTPS: http://example.com/fw.bin");
+
+        let err = check(200, manifest("0.2.0", "https://example.com/fw.bin", &SHA256[1..]), current).unwrap_err();
+        assert!(err.to_string().starts_with("Invalid SHA-256"), "{}", err);
+
+        let err = check(200, manifest("0.2.0", "https://example.com/fw.bin", &SHA256.replace('f', "g")), current).unwrap_err();
+        assert!(err.to_string().starts_with("Invalid SHA-256"), "{}", err);
+
+        assert!(check(200, manifest("0.2", "https://example.com/fw.bin", SHA256), current).is_err());
+
+        let extra = r#"{ "version": "0.2.0", "url": "https://example.com/fw.bin", "sha256": "", "signature": "" }"#;
+        assert!(check(200, extra.to_string(), current).is_err());
+    }
+
+    #[test]
+    fn parses_versions() {
+        assert_eq!("1.2.3".parse::<Version>().unwrap(), version(1, 2, 3));
+        assert_eq!(" v0.10.0 ".parse::<Version>().unwrap(), version(0, 10, 0));
+        for invalid in ["", "1.2", "1.2.3.4", "1.2.x", "1.-2.3", "1.2.3-rc1"] {
+            assert!(invalid.parse::<Version>().is_err(), "{}", invalid);
+        }
+        assert_eq!(version(1, 2, 3).to_string(), "1.2.3");
+    }
+
+    #[test]
+    fn orders_versions_numerically() {
+        assert!(version(0, 10, 0).is_newer_than(&version(0, 9, 9)));
+        assert!(version(1, 0, 0).is_newer_than(&version(0, 99, 99)));
+        assert!(version(0, 1, 10).is_newer_than(&version(0, 1, 2)));
+        assert!(!version(0, 1, 2).is_newer_than(&version(0, 1, 2)));
+        // Version of the running build
+        assert!(Version::current().is_newer_than(&version(0, 0, 0)));
+    }
+}
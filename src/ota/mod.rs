@@ -0,0 +1,103 @@
+//! Over the air firmware updates.
+//!
+//! Images alternate between the `ota_0` and `ota_1` partitions. A new image
+//! boots pending verification: it must confirm itself with `mark_valid` once
+//! it has booted, otherwise the bootloader goes back to the previous one on
+//! the next reset.
+//!
+//! The SHA-256 comes from the manifest, which is not authenticated: it catches
+//! corrupted and truncated downloads, not tampering. Anyone able to change the
+//! image can change the manifest too. Only signed images, verified by
+//! `esp_ota_end` when secure boot or `CONFIG_SECURE_SIGNED_APPS_NO_SECURE_BOOT`
+//! is enabled, protect against a compromised server.
+
+pub mod manifest;
+
+use std::ptr;
+use std::time::Duration;
+use anyhow::{bail, Result};
+use esp_idf_sys::*;
+use log::*;
+use sha2::{Digest, Sha256};
+use crate::http_client::HttpClient;
+use crate::ota::manifest::{Manifest, Version};
+
+/// Size of the `ota_0` and `ota_1` partitions
+const MAX_IMAGE_SIZE: usize = 0x1F0000;
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// Manifest of a firmware newer than the running one, if any
+pub fn check(manifest_url: &str) -> Result<Option<Manifest>> {
+    let current = Version::current();
+    let manifest = manifest::check_with(manifest_url, current, |url| HttpClient::new().get_string(url))?;
+    match &manifest {
+        Some(manifest) => info!("Firmware {} available, running {}", manifest.version, current),
+        None => info!("Firmware {} is up to date", current),
+    }
+    Ok(manifest)
+}
+
+/// Writes the image of `manifest` to the inactive slot and boots from it on
+/// the next restart. The slot is left untouched if the digest doesn't match.
+pub fn install(manifest: &Manifest) -> Result<()> {
+    let expected = manifest.digest()?;
+    let partition = unsafe { esp_ota_get_next_update_partition(ptr::null()) };
+    if partition.is_null() {
+        bail!("No OTA partition to update, check partitions.csv");
+    }
+
+    let mut handle: esp_ota_handle_t = 0;
+    esp!(unsafe { esp_ota_begin(partition, OTA_SIZE_UNKNOWN as usize, &mut handle) })?;
+
+    let mut hasher = Sha256::new();
+    let client = HttpClient::new()
+        .with_max_body_size(MAX_IMAGE_SIZE)
+        .with_request_timeout(DOWNLOAD_TIMEOUT);
+    let written = client.download(&manifest.url, |chunk| {
+        hasher.update(chunk);
+        esp!(unsafe { esp_ota_write(handle, chunk.as_ptr().cast(), chunk.len()) })?;
+        Ok(())
+    });
+    let written = match written {
+        Ok(written) => written,
+        Err(err) => {
+            unsafe { esp_ota_abort(handle) };
+            return Err(err);
+        }
+    };
+
+    if hasher.finalize().as_slice() != expected {
+        unsafe { esp_ota_abort(handle) };
+        bail!("Firmware {} doesn't match its SHA-256", manifest.version);
+    }
+    // Also validates the image header and its own checksum
+    esp!(unsafe { esp_ota_end(handle) })?;
+    esp!(unsafe { esp_ota_set_boot_partition(partition) })?;
+
+    info!("Installed firmware {} ({} bytes)", manifest.version, written);
+    Ok(())
+}
+
+/// Whether the running image was just installed and still has to pass its self-test
+pub fn is_pending_verify() -> bool {
+    let mut state: esp_ota_img_states_t = 0;
+    let running = unsafe { esp_ota_get_running_partition() };
+    // The factory partition, if any, has no state
+    let result = esp!(unsafe { esp_ota_get_state_partition(running, &mut state) });
+    result.is_ok() && state == esp_ota_img_states_t_ESP_OTA_IMG_PENDING_VERIFY
+}
+
+/// Confirms the running image, cancelling the rollback
+pub fn mark_valid() -> Result<()> {
+    esp!(unsafe { esp_ota_mark_app_valid_cancel_rollback() })?;
+    info!("Firmware {} marked valid", Version::current());
+    Ok(())
+}
+
+/// Marks the running image invalid and restarts into the previous one
+pub fn rollback() -> Result<()> {
+    warn!("Firmware {} failed its self-test, rolling back", Version::current());
+    // Only returns when there is no image to go back to
+    esp!(unsafe { esp_ota_mark_app_invalid_rollback_and_reboot() })?;
+    bail!("Rollback failed")
+}
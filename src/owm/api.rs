@@ -1,15 +1,15 @@
 use crate::config::CONFIG;
 use anyhow::{Result};
-use crate::http_client::get;
+use crate::http_client::{HttpClient, RetryPolicy};
+use crate::locations::Location;
 use crate::owm::model::WeatherData;
 
 
-pub fn fetch_owm_report() -> Result<WeatherData> {
-    let app_config = CONFIG;
-    let lat = app_config.latitude;
-    let lon = app_config.longitude;
-    let url = format!("https://api.openweathermap.org/data/3.0/onecall?lat={}&lon={}&appid={}&exclude=minutely&units=metric", lat, lon, CONFIG.owm_api_key);
-    let result = get(url)?;
-    let data: WeatherData = serde_json::from_str(&result)?;
+pub fn fetch_owm_report(location: &Location) -> Result<WeatherData> {
+    let lat = location.latitude;
+    let lon = location.longitude;
+    let url = format!("https://api.openweathermap.org/data/3.0/onecall?lat={}&lon={}&appid={}&units=metric", lat, lon, CONFIG.owm_api_key);
+    // Single attempt, `FetchScheduler` retries and counts each call against the budget
+    let data: WeatherData = HttpClient::new().with_retry(RetryPolicy::NONE).get_json(&url)?;
     Ok(data)
 }
\ No newline at end of file
@@ -0,0 +1,299 @@
+//! Deserialization helpers keeping weather reports small on the heap.
+//!
+//! Like `owm::model`, kept free of anything target specific so
+//! `tools/model-bench` can measure it on the host.
+
+use std::borrow::Cow;
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::Deref;
+use serde::de::{IgnoredAny, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Every `main`, `description` and `icon` OpenWeather sends in English,
+/// see https://openweathermap.org/weather-conditions
+const CONDITION_STRINGS: &[&str] = &[
+    // Groups
+    "Thunderstorm", "Drizzle", "Rain", "Snow", "Mist", "Smoke", "Haze", "Dust", "Fog",
+    "Sand", "Ash", "Squall", "Tornado", "Clear", "Clouds",
+    // Descriptions
+    "thunderstorm with light rain", "thunderstorm with rain", "thunderstorm with heavy rain",
+    "light thunderstorm", "thunderstorm", "heavy thunderstorm", "ragged thunderstorm",
+    "thunderstorm with light drizzle", "thunderstorm with drizzle", "thunderstorm with heavy drizzle",
+    "light intensity drizzle", "drizzle", "heavy intensity drizzle", "light intensity drizzle rain",
+    "drizzle rain", "heavy intensity drizzle rain", "shower rain and drizzle",
+    "heavy shower rain and drizzle", "shower drizzle",
+    "light rain", "moderate rain", "heavy intensity rain", "very heavy rain", "extreme rain",
+    "freezing rain", "light intensity shower rain", "shower rain", "heavy intensity shower rain",
+    "ragged shower rain",
+    "light snow", "snow", "heavy snow", "sleet", "light shower sleet", "shower sleet",
+    "light rain and snow", "rain and snow", "light shower snow", "shower snow", "heavy shower snow",
+    "mist", "smoke", "haze", "sand/dust whirls", "fog", "sand", "dust", "volcanic ash", "squalls",
+    "tornado",
+    "clear sky", "few clouds", "scattered clouds", "broken clouds", "overcast clouds",
+    // Icons
+    "01d", "01n", "02d", "02n", "03d", "03n", "04d", "04n", "09d", "09n",
+    "10d", "10n", "11d", "11n", "13d", "13n", "50d", "50n",
+];
+
+/// String from a small known vocabulary. Known values point to a static copy
+/// instead of allocating, anything else is kept as is.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Interned(Cow<'static, str>);
+
+impl Interned {
+    pub fn new(value: &str) -> Self {
+        match CONDITION_STRINGS.iter().find(|known| **known == value) {
+            Some(known) => Interned(Cow::Borrowed(known)),
+            None => Interned(Cow::Owned(value.to_string())),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Whether no heap memory is used for this value
+    pub fn is_static(&self) -> bool {
+        matches!(self.0, Cow::Borrowed(_))
+    }
+}
+
+impl Deref for Interned {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Interned {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Serialize for Interned {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Interned {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct InternedVisitor;
+
+        impl<'de> Visitor<'de> for InternedVisitor {
+            type Value = Interned;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a string")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<Interned, E> {
+                Ok(Interned::new(value))
+            }
+        }
+
+        deserializer.deserialize_str(InternedVisitor)
+    }
+}
+
+/// Sequence of at most `N` elements, allocated once at its final size.
+/// Elements past `N` are skipped without being built.
+pub fn bounded<'de, D, T, const N: usize>(deserializer: D) -> Result<Option<Vec<T>>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: Deserialize<'de>,
+{
+    Option::<BoundedSeq<T, N>>::deserialize(deserializer).map(|items| items.map(|items| items.0))
+}
+
+/// Order of the elements kept by `ranked`, `None` ranking below everything
+pub trait Rank {
+    fn rank(&self) -> Option<u8>;
+}
+
+/// Sequence of at most `N` elements, in a `heapless::Vec` for short lists that
+/// don't deserve an allocation. Once full, a new element replaces the lowest
+/// ranked one if it ranks higher. The first element is always kept and the
+/// others stay in the order they were listed, so earlier elements win ties.
+pub fn ranked<'de, D, T, const N: usize>(deserializer: D) -> Result<heapless::Vec<T, N>, D::Error>
+    where
+        D: Deserializer<'de>,
+        T: Deserialize<'de> + Rank,
+{
+    struct RankedVisitor<T, const N: usize>(PhantomData<T>);
+
+    impl<'de, T: Deserialize<'de> + Rank, const N: usize> Visitor<'de> for RankedVisitor<T, N> {
+        type Value = heapless::Vec<T, N>;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("a sequence")
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<heapless::Vec<T, N>, A::Error> {
+            let mut items = heapless::Vec::new();
+            while let Some(item) = seq.next_element::<T>()? {
+                if !items.is_full() {
+                    // Can't fail, there is room left
+                    let _ = items.push(item);
+                    continue;
+                }
+                let lowest = items.iter()
+                    .enumerate()
+                    .skip(1)
+                    // Reversed so the last listed of equally ranked elements goes first
+                    .rev()
+                    .min_by_key(|(_, kept)| kept.rank())
+                    .map(|(index, _)| index);
+                if let Some(lowest) = lowest.filter(|lowest| item.rank() > items[*lowest].rank()) {
+                    items.remove(lowest);
+                    let _ = items.push(item);
+                }
+            }
+            Ok(items)
+        }
+    }
+
+    deserializer.deserialize_seq(RankedVisitor(PhantomData))
+}
+
+/// String of at most `N` bytes, cut at a character boundary. Kept inline instead
+/// of allocating, the stream being parsed can't be borrowed from.
+pub fn truncated_str<'de, D, const N: usize>(deserializer: D) -> Result<heapless::String<N>, D::Error>
+    where
+        D: Deserializer<'de>,
+{
+    struct TruncatedStrVisitor<const N: usize>;
+
+    impl<'de, const N: usize> Visitor<'de> for TruncatedStrVisitor<N> {
+        type Value = heapless::String<N>;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("a string")
+        }
+
+        fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<heapless::String<N>, E> {
+            let mut end = value.len().min(N);
+            while !value.is_char_boundary(end) {
+                end -= 1;
+            }
+            let mut truncated = heapless::String::new();
+            // Can't fail, the slice fits
+            let _ = truncated.push_str(&value[..end]);
+            Ok(truncated)
+        }
+    }
+
+    deserializer.deserialize_str(TruncatedStrVisitor)
+}
+
+/// `bounded` as a type, so it can sit inside an `Option`
+struct BoundedSeq<T, const N: usize>(Vec<T>);
+
+impl<'de, T: Deserialize<'de>, const N: usize> Deserialize<'de> for BoundedSeq<T, N> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct BoundedVisitor<T, const N: usize>(PhantomData<T>);
+
+        impl<'de, T: Deserialize<'de>, const N: usize> Visitor<'de> for BoundedVisitor<T, N> {
+            type Value = Vec<T>;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a sequence")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<T>, A::Error> {
+                let mut items = Vec::with_capacity(seq.size_hint().unwrap_or(N).min(N));
+                while items.len() < N {
+                    match seq.next_element()? {
+                        Some(item) => items.push(item),
+                        None => return Ok(items),
+                    }
+                }
+                while seq.next_element::<IgnoredAny>()?.is_some() {}
+                Ok(items)
+            }
+        }
+
+        deserializer.deserialize_seq(BoundedVisitor::<T, N>(PhantomData)).map(BoundedSeq)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+    use super::*;
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Ranked(u8, char);
+
+    impl Rank for Ranked {
+        fn rank(&self) -> Option<u8> {
+            (self.0 > 0).then_some(self.0)
+        }
+    }
+
+    #[derive(Deserialize)]
+    struct Entry {
+        #[serde(deserialize_with = "ranked")]
+        items: heapless::Vec<Ranked, 3>,
+    }
+
+    #[derive(Deserialize)]
+    struct Text {
+        #[serde(deserialize_with = "truncated_str")]
+        text: heapless::String<8>,
+    }
+
+    fn ranked_letters(json: &str) -> String {
+        let entry: Entry = serde_json::from_str(&format!(r#"{{"items":{}}}"#, json)).unwrap();
+        entry.items.iter().map(|item| item.1).collect()
+    }
+
+    fn truncate(text: &str) -> String {
+        let text: Text = serde_json::from_str(&serde_json::json!({ "text": text }).to_string()).unwrap();
+        text.text.to_string()
+    }
+
+    #[test]
+    fn ranked_keeps_short_lists() {
+        assert_eq!(ranked_letters("[]"), "");
+        assert_eq!(ranked_letters(r#"[[1,"a"],[0,"b"],[5,"c"]]"#), "abc");
+    }
+
+    #[test]
+    fn ranked_keeps_the_highest_after_the_first() {
+        assert_eq!(ranked_letters(r#"[[0,"a"],[1,"b"],[2,"c"],[9,"d"],[3,"e"]]"#), "ade");
+        // The first one stays whatever its rank
+        assert_eq!(ranked_letters(r#"[[0,"a"],[5,"b"],[5,"c"],[6,"d"]]"#), "abd");
+        // Unranked elements never replace anything
+        assert_eq!(ranked_letters(r#"[[1,"a"],[0,"b"],[0,"c"],[0,"d"]]"#), "abc");
+    }
+
+    #[test]
+    fn ranked_prefers_earlier_elements_on_ties() {
+        assert_eq!(ranked_letters(r#"[[1,"a"],[4,"b"],[4,"c"],[4,"d"],[4,"e"]]"#), "abc");
+        assert_eq!(ranked_letters(r#"[[1,"a"],[2,"b"],[7,"c"],[7,"d"]]"#), "acd");
+    }
+
+    #[test]
+    fn truncated_str_cuts_at_character_boundaries() {
+        assert_eq!(truncate(""), "");
+        assert_eq!(truncate("Showers"), "Showers");
+        assert_eq!(truncate("Rainy day"), "Rainy da");
+        // "é" takes 2 bytes, the 8th byte is in its middle
+        assert_eq!(truncate("Journée"), "Journée");
+        assert_eq!(truncate("Orageuse"), "Orageuse");
+        assert_eq!(truncate("Ensoleillé"), "Ensoleil");
+        assert_eq!(truncate("Averséé"), "Aversé");
+        assert_eq!(truncate("Neige ❄️"), "Neige ");
+    }
+
+    #[test]
+    fn interns_known_strings() {
+        assert!(Interned::new("light rain").is_static());
+        assert!(!Interned::new("pluie légère").is_static());
+        assert_eq!(Interned::new("pluie légère").as_str(), "pluie légère");
+    }
+}
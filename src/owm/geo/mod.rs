@@ -0,0 +1,61 @@
+//! OpenWeather Geocoding API, https://openweathermap.org/api/geocoding-api
+
+mod place;
+
+use anyhow::{Context, Result};
+use crate::config::CONFIG;
+use crate::http_client::HttpClient;
+use crate::storage::Storage;
+
+pub use place::{Place, Query};
+use place::{cached, encode, first_place};
+
+// NVS keys of the last resolved query and coordinates
+const QUERY_CACHE_KEY: &str = "geo_query";
+const REVERSE_CACHE_KEY: &str = "geo_reverse";
+
+/// Request URL of `query`
+fn url(query: Query<'_>) -> String {
+    match query {
+        Query::City(city) => format!(
+            "https://api.openweathermap.org/geo/1.0/direct?q={}&limit=1&appid={}",
+            encode(city), CONFIG.owm_api_key,
+        ),
+        Query::Zip(zip) => format!(
+            "https://api.openweathermap.org/geo/1.0/zip?zip={}&appid={}",
+            encode(zip), CONFIG.owm_api_key,
+        ),
+    }
+}
+
+/// Coordinates of the best match for `query`
+pub fn direct(query: Query<'_>) -> Result<Place> {
+    let client = HttpClient::new();
+    let url = url(query);
+    match query {
+        Query::City(city) => first_place(client.get_json(&url)?).with_context(|| format!("No place named '{}'", city)),
+        // Unknown postal codes are a 404, the response is a single place otherwise
+        Query::Zip(_) => client.get_json(&url),
+    }
+}
+
+/// Place at the given coordinates
+pub fn reverse(lat: f32, lon: f32) -> Result<Place> {
+    let url = format!(
+        "https://api.openweathermap.org/geo/1.0/reverse?lat={}&lon={}&limit=1&appid={}",
+        lat, lon, CONFIG.owm_api_key,
+    );
+    first_place(HttpClient::new().get_json(&url)?).with_context(|| format!("No place at {},{}", lat, lon))
+}
+
+/// Like `direct`, through a cache in NVS so the query is only sent once
+pub fn resolve(storage: &mut Storage, query: &str) -> Result<Place> {
+    let query = query.trim();
+    cached(storage, QUERY_CACHE_KEY, query, || direct(Query::parse(query)))
+}
+
+/// Like `reverse`, through a cache in NVS so the coordinates are only sent once
+pub fn resolve_coordinates(storage: &mut Storage, lat: f32, lon: f32) -> Result<Place> {
+    let key = format!("{:.4},{:.4}", lat, lon);
+    cached(storage, REVERSE_CACHE_KEY, &key, || reverse(lat, lon))
+}
@@ -0,0 +1,241 @@
+//! Geocoding responses and their cache, independent of the network so they
+//! can be tested on the host.
+
+use anyhow::{Context, Result};
+use log::*;
+use serde::{Deserialize, Serialize};
+use crate::storage::KeyValue;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Place {
+    pub name: String,
+    pub lat: f32,
+    pub lon: f32,
+    pub country: String,
+    /// Missing for postal codes and most countries without states
+    #[serde(default)]
+    pub state: Option<String>,
+}
+
+impl Place {
+    /// e.g. "Montreal, Quebec, CA"
+    pub fn display_name(&self) -> String {
+        match &self.state {
+            Some(state) => format!("{}, {}, {}", self.name, state, self.country),
+            None => format!("{}, {}", self.name, self.country),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Query<'a> {
+    /// `{city name},{state code},{country code}`, the codes being optional
+    City(&'a str),
+    /// `{zip or postal code},{country code}`
+    Zip(&'a str),
+}
+
+impl<'a> Query<'a> {
+    /// Postal codes are told apart from city names by their digits
+    pub fn parse(query: &'a str) -> Self {
+        let query = query.trim();
+        let first = query.split(',').next().unwrap_or_default();
+        match first.chars().any(|char| char.is_ascii_digit()) {
+            true => Query::Zip(query),
+            false => Query::City(query),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedPlace {
+    key: String,
+    place: Place,
+}
+
+/// Keeps a single entry per storage key, the configuration rarely changes
+pub fn cached<S: KeyValue>(storage: &mut S, storage_key: &str, key: &str, fetch: impl FnOnce() -> Result<Place>) -> Result<Place> {
+    match storage.get_string(storage_key).map(|value| value.map(|json| serde_json::from_str::<CachedPlace>(&json))) {
+        Ok(Some(Ok(cached))) if cached.key == key => return Ok(cached.place),
+        Ok(_) => {}
+        Err(err) => warn!("Reading the geocoding cache failed: {:#}", err),
+    }
+
+    let place = fetch()?;
+    info!("Geocoded '{}' as {} ({},{})", key, place.display_name(), place.lat, place.lon);
+
+    let entry = CachedPlace { key: key.to_string(), place };
+    let stored = serde_json::to_string(&entry)
+        .map_err(anyhow::Error::from)
+        .and_then(|json| storage.set_string(storage_key, &json));
+    if let Err(err) = stored {
+        warn!("Caching '{}' failed: {:#}", key, err);
+    }
+
+    Ok(entry.place)
+}
+
+/// Best match of a response listing places
+pub fn first_place(places: Vec<Place>) -> Result<Place> {
+    places.into_iter().next().context("Empty geocoding response")
+}
+
+/// Percent-encodes `value` for a query string, keeping the commas between its parts
+pub fn encode(value: &str) -> String {
+    value.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b',' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::collections::HashMap;
+    use anyhow::bail;
+    use super::*;
+
+    const DIRECT: &str = include_str!("fixtures/direct.json");
+    const ZIP: &str = include_str!("fixtures/zip.json");
+    const REVERSE: &str = include_str!("fixtures/reverse.json");
+    const EMPTY: &str = include_str!("fixtures/empty.json");
+
+    /// NVS stand-in, optionally failing every access
+    #[derive(Default)]
+    struct MemoryStore {
+        values: HashMap<String, String>,
+        broken: bool,
+    }
+
+    impl KeyValue for MemoryStore {
+        fn get_string(&self, key: &str) -> Result<Option<String>> {
+            if self.broken {
+                bail!("NVS unavailable");
+            }
+            Ok(self.values.get(key).cloned())
+        }
+
+        fn set_string(&mut self, key: &str, value: &str) -> Result<()> {
+            if self.broken {
+                bail!("NVS unavailable");
+            }
+            self.values.insert(key.to_string(), value.to_string());
+            Ok(())
+        }
+    }
+
+    fn montreal() -> Place {
+        Place {
+            name: "Montreal".to_string(),
+            lat: 45.503_18,
+            lon: -73.569_81,
+            country: "CA".to_string(),
+            state: Some("Quebec".to_string()),
+        }
+    }
+
+    #[test]
+    fn parses_direct_response() {
+        let place = first_place(serde_json::from_str(DIRECT).unwrap()).unwrap();
+        assert_eq!(place, montreal());
+        assert_eq!(place.display_name(), "Montreal, Quebec, CA");
+    }
+
+    #[test]
+    fn parses_zip_response() {
+        let place: Place = serde_json::from_str(ZIP).unwrap();
+        assert_eq!(place.state, None);
+        assert_eq!((place.lat, place.lon), (45.5118, -73.5659));
+        assert_eq!(place.display_name(), "Montreal, CA");
+    }
+
+    #[test]
+    fn parses_reverse_response() {
+        let place = first_place(serde_json::from_str(REVERSE).unwrap()).unwrap();
+        assert_eq!(place.display_name(), "City of Westminster, England, GB");
+    }
+
+    #[test]
+    fn empty_response_is_an_error() {
+        let err = first_place(serde_json::from_str(EMPTY).unwrap()).unwrap_err();
+        assert_eq!(err.to_string(), "Empty geocoding response");
+    }
+
+    #[test]
+    fn tells_postal_codes_from_cities() {
+        assert_eq!(Query::parse("Montreal,QC,CA"), Query::City("Montreal,QC,CA"));
+        assert_eq!(Query::parse(" H2X,CA "), Query::Zip("H2X,CA"));
+        assert_eq!(Query::parse("10001,US"), Query::Zip("10001,US"));
+        // Digits in the country code don't make a postal code
+        assert_eq!(Query::parse("Paris,75"), Query::City("Paris,75"));
+    }
+
+    #[test]
+    fn encodes_query_strings() {
+        assert_eq!(encode("Montreal,QC,CA"), "Montreal,QC,CA");
+        assert_eq!(encode("Saint-Jérôme,QC"), "Saint-J%C3%A9r%C3%B4me,QC");
+        assert_eq!(encode("New York&x=1"), "New%20York%26x%3D1");
+    }
+
+    #[test]
+    fn cache_is_filled_on_first_use() {
+        let mut storage = MemoryStore::default();
+        let place = cached(&mut storage, "geo_query", "Montreal", || Ok(montreal())).unwrap();
+        assert_eq!(place, montreal());
+
+        let stored: CachedPlace = serde_json::from_str(&storage.values["geo_query"]).unwrap();
+        assert_eq!(stored.key, "Montreal");
+        assert_eq!(stored.place, montreal());
+    }
+
+    #[test]
+    fn cache_hit_skips_the_request() {
+        let mut storage = MemoryStore::default();
+        cached(&mut storage, "geo_query", "Montreal", || Ok(montreal())).unwrap();
+
+        let place = cached(&mut storage, "geo_query", "Montreal", || panic!("fetched again")).unwrap();
+        assert_eq!(place, montreal());
+    }
+
+    #[test]
+    fn changed_query_replaces_the_entry() {
+        let mut storage = MemoryStore::default();
+        cached(&mut storage, "geo_query", "Montreal", || Ok(montreal())).unwrap();
+
+        let zip: Place = serde_json::from_str(ZIP).unwrap();
+        let fetches = Cell::new(0);
+        let fetch = || {
+            fetches.set(fetches.get() + 1);
+            Ok(zip.clone())
+        };
+        assert_eq!(cached(&mut storage, "geo_query", "H2X,CA", fetch).unwrap(), zip);
+        assert_eq!(cached(&mut storage, "geo_query", "H2X,CA", fetch).unwrap(), zip);
+        assert_eq!(fetches.get(), 1);
+    }
+
+    #[test]
+    fn corrupt_entry_is_fetched_again() {
+        let mut storage = MemoryStore::default();
+        storage.values.insert("geo_query".to_string(), "{\"key\":\"Montreal\"".to_string());
+        assert_eq!(cached(&mut storage, "geo_query", "Montreal", || Ok(montreal())).unwrap(), montreal());
+        assert!(storage.values["geo_query"].ends_with('}'));
+    }
+
+    #[test]
+    fn failed_request_leaves_the_cache_alone() {
+        let mut storage = MemoryStore::default();
+        cached(&mut storage, "geo_query", "Montreal", || Ok(montreal())).unwrap();
+        let before = storage.values.clone();
+
+        assert!(cached(&mut storage, "geo_query", "Paris", || bail!("offline")).is_err());
+        assert_eq!(storage.values, before);
+    }
+
+    #[test]
+    fn broken_storage_still_resolves() {
+        let mut storage = MemoryStore { broken: true, ..Default::default() };
+        assert_eq!(cached(&mut storage, "geo_query", "Montreal", || Ok(montreal())).unwrap(), montreal());
+    }
+}
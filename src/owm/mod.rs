@@ -1,3 +1,6 @@
 pub mod api;
+pub mod de;
+pub mod geo;
 pub mod model;
-pub mod icons;
\ No newline at end of file
+pub mod icons;
+pub mod nowcast;
\ No newline at end of file
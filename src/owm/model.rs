@@ -1,15 +1,37 @@
+//! OneCall report, reduced to the fields widgets render.
+//!
+//! Parsed straight from the response stream, see `http_client::HttpClient::get_json`.
+//! Condition strings are interned and series bounded, see `owm::de`.
+//! Kept free of anything target specific for `tools/model-bench`.
+
 use serde::{Deserialize, Serialize};
+use crate::owm::de::{bounded, ranked, truncated_str, Interned, Rank};
+
+/// Minutes of precipitation forecast, the current one included
+pub const MAX_MINUTES: usize = 61;
+pub const MAX_HOURS: usize = 48;
+pub const MAX_DAYS: usize = 8;
+pub const MAX_ALERTS: usize = 4;
+/// Conditions kept per entry: the main one, listed first, and the most severe others
+pub const MAX_CONDITIONS: usize = 2;
+/// Bytes kept of the daily summary, longer ones are cut
+pub const MAX_SUMMARY: usize = 160;
+
+pub type Conditions = heapless::Vec<WeatherCondition, MAX_CONDITIONS>;
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct WeatherData {
     pub lat: f32,
     pub lon: f32,
-    pub timezone: String,
     pub timezone_offset: i64,
     pub current: Option<CurrentWeather>,
+    #[serde(default, deserialize_with = "bounded::<_, _, MAX_MINUTES>")]
     pub minutely: Option<Vec<MinuteForecast>>,
+    #[serde(default, deserialize_with = "bounded::<_, _, MAX_HOURS>")]
     pub hourly: Option<Vec<HourlyForecast>>,
+    #[serde(default, deserialize_with = "bounded::<_, _, MAX_DAYS>")]
     pub daily: Option<Vec<DailyForecast>>,
+    #[serde(default, deserialize_with = "bounded::<_, _, MAX_ALERTS>")]
     pub alerts: Option<Vec<Alert>>,
 }
 
@@ -20,16 +42,12 @@ pub struct CurrentWeather {
     pub sunset: u64,
     pub temp: f32,
     pub feels_like: f32,
-    pub pressure: u16,
-    pub humidity: u8,
-    pub dew_point: f32,
-    pub uvi: f32,
     pub clouds: u8,
-    pub visibility: u16,
     pub wind_speed: f32,
     pub wind_deg: u16,
     pub wind_gust: Option<f32>,
-    pub weather: Vec<WeatherCondition>,
+    #[serde(deserialize_with = "ranked")]
+    pub weather: Conditions,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -42,17 +60,12 @@ pub struct MinuteForecast {
 pub struct HourlyForecast {
     pub dt: u64,
     pub temp: f32,
-    pub feels_like: f32,
-    pub pressure: u16,
-    pub humidity: u8,
-    pub dew_point: f32,
-    pub uvi: f32,
     pub clouds: u8,
-    pub visibility: u16,
     pub wind_speed: f32,
     pub wind_deg: u16,
     pub wind_gust: Option<f32>,
-    pub weather: Vec<WeatherCondition>,
+    #[serde(deserialize_with = "ranked")]
+    pub weather: Conditions,
     pub pop: f32,
 }
 
@@ -64,47 +77,32 @@ pub struct DailyForecast {
     pub moonrise: u64,
     pub moonset: u64,
     pub moon_phase: f32,
-    pub summary: String,
+    #[serde(default, deserialize_with = "truncated_str")]
+    pub summary: heapless::String<MAX_SUMMARY>,
     pub temp: Temperature,
-    pub feels_like: FeelsLikeTemperature,
-    pub pressure: u16,
-    pub humidity: u8,
-    pub dew_point: f32,
     pub wind_speed: f32,
     pub wind_deg: u16,
     pub wind_gust: Option<f32>,
-    pub weather: Vec<WeatherCondition>,
+    #[serde(deserialize_with = "ranked")]
+    pub weather: Conditions,
     pub clouds: u8,
     pub pop: f32,
-    pub rain: Option<f32>,
-    pub snow: Option<f32>,
-    pub uvi: f32,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Temperature {
-    pub morn: f32,
     pub day: f32,
-    pub eve: f32,
     pub night: f32,
     pub min: f32,
     pub max: f32,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
-pub struct FeelsLikeTemperature {
-    pub morn: f32,
-    pub day: f32,
-    pub eve: f32,
-    pub night: f32,
-}
-
 #[derive(Debug, Deserialize, Serialize)]
 pub struct WeatherCondition {
     pub id: u16,
-    pub main: String,
-    pub description: String,
-    pub icon: String,
+    pub main: Interned,
+    pub description: Interned,
+    pub icon: Interned,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -114,10 +112,9 @@ pub struct Alert {
     pub start: u64,
     pub end: u64,
     pub description: String,
-    pub tags: Vec<String>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum WeatherConditionId {
     ThunderstormWithLightRain,
     ThunderstormWithRain,
@@ -176,65 +173,181 @@ pub enum WeatherConditionId {
     OvercastClouds,
 }
 
+/// OpenWeather id of each condition and how severe it is, from 0 for a clear
+/// sky to 100 for a tornado. See https://openweathermap.org/weather-conditions
+const CONDITIONS: [(u16, WeatherConditionId, u8); 55] = [
+    (200, WeatherConditionId::ThunderstormWithLightRain, 74),
+    (201, WeatherConditionId::ThunderstormWithRain, 76),
+    (202, WeatherConditionId::ThunderstormWithHeavyRain, 80),
+    (210, WeatherConditionId::LightThunderstorm, 72),
+    (211, WeatherConditionId::Thunderstorm, 76),
+    (212, WeatherConditionId::HeavyThunderstorm, 80),
+    (221, WeatherConditionId::RaggedThunderstorm, 78),
+    (230, WeatherConditionId::ThunderstormWithLightDrizzle, 73),
+    (231, WeatherConditionId::ThunderstormWithDrizzle, 75),
+    (232, WeatherConditionId::ThunderstormWithHeavyDrizzle, 77),
+    (300, WeatherConditionId::LightIntensityDrizzle, 32),
+    (301, WeatherConditionId::Drizzle, 35),
+    (302, WeatherConditionId::HeavyIntensityDrizzle, 40),
+    (310, WeatherConditionId::LightIntensityDrizzleRain, 33),
+    (311, WeatherConditionId::DrizzleRain, 37),
+    (312, WeatherConditionId::HeavyIntensityDrizzleRain, 41),
+    (313, WeatherConditionId::ShowerRainAndDrizzle, 38),
+    (314, WeatherConditionId::HeavyShowerRainAndDrizzle, 42),
+    (321, WeatherConditionId::ShowerDrizzle, 36),
+    (500, WeatherConditionId::LightRain, 45),
+    (501, WeatherConditionId::ModerateRain, 50),
+    (502, WeatherConditionId::HeavyIntensityRain, 54),
+    (503, WeatherConditionId::VeryHeavyRain, 56),
+    (504, WeatherConditionId::ExtremeRain, 58),
+    (511, WeatherConditionId::FreezingRain, 68),
+    (520, WeatherConditionId::LightIntensityShowerRain, 46),
+    (521, WeatherConditionId::ShowerRain, 50),
+    (522, WeatherConditionId::HeavyIntensityShowerRain, 53),
+    (531, WeatherConditionId::RaggedShowerRain, 52),
+    (600, WeatherConditionId::LightSnow, 62),
+    (601, WeatherConditionId::Snow, 66),
+    (602, WeatherConditionId::HeavySnow, 70),
+    (611, WeatherConditionId::Sleet, 64),
+    (612, WeatherConditionId::LightShowerSleet, 60),
+    (613, WeatherConditionId::ShowerSleet, 63),
+    (615, WeatherConditionId::LightRainAndSnow, 60),
+    (616, WeatherConditionId::RainAndSnow, 63),
+    (620, WeatherConditionId::LightShowerSnow, 61),
+    (621, WeatherConditionId::ShowerSnow, 65),
+    (622, WeatherConditionId::HeavyShowerSnow, 69),
+    (701, WeatherConditionId::Mist, 20),
+    (711, WeatherConditionId::Smoke, 26),
+    (721, WeatherConditionId::Haze, 22),
+    (731, WeatherConditionId::SandOrDustWhirls, 30),
+    (741, WeatherConditionId::Fog, 25),
+    (751, WeatherConditionId::Sand, 28),
+    (761, WeatherConditionId::Dust, 27),
+    (762, WeatherConditionId::VolcanicAsh, 85),
+    (771, WeatherConditionId::Squalls, 90),
+    (781, WeatherConditionId::Tornado, 100),
+    (800, WeatherConditionId::ClearSky, 0),
+    (801, WeatherConditionId::FewClouds, 4),
+    (802, WeatherConditionId::ScatteredClouds, 6),
+    (803, WeatherConditionId::BrokenClouds, 8),
+    (804, WeatherConditionId::OvercastClouds, 10),
+];
+
+impl WeatherConditionId {
+    /// Every condition OpenWeather documents
+    pub fn all() -> impl Iterator<Item = WeatherConditionId> {
+        CONDITIONS.iter().map(|(_, condition, _)| *condition)
+    }
+
+    pub fn from_id(id: u16) -> Option<WeatherConditionId> {
+        CONDITIONS.iter().find(|(known, _, _)| *known == id).map(|(_, condition, _)| *condition)
+    }
+
+    pub fn id(&self) -> u16 {
+        self.row().0
+    }
+
+    /// Higher for weather that matters more, to pick one of several reported conditions
+    pub fn severity(&self) -> u8 {
+        self.row().2
+    }
+
+    fn row(&self) -> &'static (u16, WeatherConditionId, u8) {
+        // Every variant has a row
+        CONDITIONS.iter().find(|(_, condition, _)| condition == self).unwrap()
+    }
+}
+
+impl Rank for WeatherCondition {
+    fn rank(&self) -> Option<u8> {
+        self.get_condition().map(|condition| condition.severity())
+    }
+}
+
 impl WeatherCondition {
     pub fn get_condition(&self) -> Option<WeatherConditionId> {
-        match self.id {
-            200 => Some(WeatherConditionId::ThunderstormWithLightRain),
-            201 => Some(WeatherConditionId::ThunderstormWithRain),
-            202 => Some(WeatherConditionId::ThunderstormWithHeavyRain),
-            210 => Some(WeatherConditionId::LightThunderstorm),
-            211 => Some(WeatherConditionId::Thunderstorm),
-            212 => Some(WeatherConditionId::HeavyThunderstorm),
-            221 => Some(WeatherConditionId::RaggedThunderstorm),
-            230 => Some(WeatherConditionId::ThunderstormWithLightDrizzle),
-            231 => Some(WeatherConditionId::ThunderstormWithDrizzle),
-            232 => Some(WeatherConditionId::ThunderstormWithHeavyDrizzle),
-            300 => Some(WeatherConditionId::LightIntensityDrizzle),
-            301 => Some(WeatherConditionId::Drizzle),
-            302 => Some(WeatherConditionId::HeavyIntensityDrizzle),
-            310 => Some(WeatherConditionId::LightIntensityDrizzleRain),
-            311 => Some(WeatherConditionId::DrizzleRain),
-            312 => Some(WeatherConditionId::HeavyIntensityDrizzleRain),
-            313 => Some(WeatherConditionId::ShowerRainAndDrizzle),
-            314 => Some(WeatherConditionId::HeavyShowerRainAndDrizzle),
-            321 => Some(WeatherConditionId::ShowerDrizzle),
-            500 => Some(WeatherConditionId::LightRain),
-            501 => Some(WeatherConditionId::ModerateRain),
-            502 => Some(WeatherConditionId::HeavyIntensityRain),
-            503 => Some(WeatherConditionId::VeryHeavyRain),
-            504 => Some(WeatherConditionId::ExtremeRain),
-            511 => Some(WeatherConditionId::FreezingRain),
-            520 => Some(WeatherConditionId::LightIntensityShowerRain),
-            521 => Some(WeatherConditionId::ShowerRain),
-            522 => Some(WeatherConditionId::HeavyIntensityShowerRain),
-            531 => Some(WeatherConditionId::RaggedShowerRain),
-            600 => Some(WeatherConditionId::LightSnow),
-            601 => Some(WeatherConditionId::Snow),
-            602 => Some(WeatherConditionId::HeavySnow),
-            611 => Some(WeatherConditionId::Sleet),
-            612 => Some(WeatherConditionId::LightShowerSleet),
-            613 => Some(WeatherConditionId::ShowerSleet),
-            615 => Some(WeatherConditionId::LightRainAndSnow),
-            616 => Some(WeatherConditionId::RainAndSnow),
-            620 => Some(WeatherConditionId::LightShowerSnow),
-            621 => Some(WeatherConditionId::ShowerSnow),
-            622 => Some(WeatherConditionId::HeavyShowerSnow),
-            701 => Some(WeatherConditionId::Mist),
-            711 => Some(WeatherConditionId::Smoke),
-            721 => Some(WeatherConditionId::Haze),
-            731 => Some(WeatherConditionId::SandOrDustWhirls),
-            741 => Some(WeatherConditionId::Fog),
-            751 => Some(WeatherConditionId::Sand),
-            761 => Some(WeatherConditionId::Dust),
-            762 => Some(WeatherConditionId::VolcanicAsh),
-            771 => Some(WeatherConditionId::Squalls),
-            781 => Some(WeatherConditionId::Tornado),
-            800 => Some(WeatherConditionId::ClearSky),
-            801 => Some(WeatherConditionId::FewClouds),
-            802 => Some(WeatherConditionId::ScatteredClouds),
-            803 => Some(WeatherConditionId::BrokenClouds),
-            804 => Some(WeatherConditionId::OvercastClouds),
+        WeatherConditionId::from_id(self.id)
+    }
+
+    /// Whether it is night at the location, from the `d` or `n` suffix of the icon code
+    pub fn is_night(&self) -> Option<bool> {
+        match self.icon.as_bytes().last() {
+            Some(b'n') => Some(true),
+            Some(b'd') => Some(false),
             _ => None,
         }
     }
-}
\ No newline at end of file
+}
+
+/// Most severe of the reported conditions OpenWeather documents
+pub fn most_severe(conditions: &[WeatherCondition]) -> Option<(&WeatherCondition, WeatherConditionId)> {
+    // Reversed so the first one listed wins a tie, max_by_key keeps the last
+    conditions.iter()
+        .rev()
+        .filter_map(|weather| weather.get_condition().map(|condition| (weather, condition)))
+        .max_by_key(|(_, condition)| condition.severity())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use super::*;
+
+    fn condition(id: u16, icon: &str) -> serde_json::Value {
+        json!({ "id": id, "main": "", "description": "", "icon": icon })
+    }
+
+    fn current(weather: Vec<serde_json::Value>) -> CurrentWeather {
+        serde_json::from_value(json!({
+            "dt": 1_700_000_000, "sunrise": 1_699_990_000, "sunset": 1_700_030_000, "temp": 4.2,
+            "feels_like": 1.5, "clouds": 75, "wind_speed": 5.1, "wind_deg": 250, "weather": weather,
+        })).unwrap()
+    }
+
+    fn ids(conditions: &Conditions) -> Vec<u16> {
+        conditions.iter().map(|condition| condition.id).collect()
+    }
+
+    #[test]
+    fn severe_conditions_survive_the_cap() {
+        // Clouds first, the thunderstorm listed last
+        let weather = current(vec![condition(803, "04d"), condition(300, "09d"), condition(500, "10d"), condition(211, "11d")]);
+        assert_eq!(ids(&weather.weather), [803, 211]);
+
+        let (_, most_severe) = most_severe(&weather.weather).unwrap();
+        assert_eq!(most_severe, WeatherConditionId::Thunderstorm);
+    }
+
+    #[test]
+    fn main_condition_is_always_kept() {
+        let weather = current(vec![condition(781, "50d"), condition(500, "10d"), condition(800, "01d")]);
+        assert_eq!(ids(&weather.weather), [781, 500]);
+        assert_eq!(most_severe(&weather.weather).unwrap().1, WeatherConditionId::Tornado);
+    }
+
+    #[test]
+    fn undocumented_conditions_give_way() {
+        let weather = current(vec![condition(999, "01d"), condition(998, "01d"), condition(701, "50d")]);
+        assert_eq!(ids(&weather.weather), [999, 701]);
+        assert_eq!(most_severe(&weather.weather).unwrap().1, WeatherConditionId::Mist);
+    }
+
+    #[test]
+    fn long_summaries_are_cut() {
+        let day = |summary: &str| -> DailyForecast {
+            serde_json::from_value(json!({
+                "dt": 1_700_000_000, "sunrise": 0, "sunset": 0, "moonrise": 0, "moonset": 0, "moon_phase": 0.5,
+                "summary": summary, "temp": { "day": 5.0, "night": 1.0, "min": 0.0, "max": 6.0 },
+                "wind_speed": 3.0, "wind_deg": 90, "weather": [condition(500, "10d")], "clouds": 90, "pop": 0.8,
+            })).unwrap()
+        };
+
+        let summary = "Expect a day of partly cloudy with rain";
+        assert_eq!(day(summary).summary, summary);
+
+        let long = "There will be rain until morning, then partly cloudy. ".repeat(4);
+        let cut = day(&long).summary;
+        assert_eq!(cut.len(), MAX_SUMMARY);
+        assert!(long.starts_with(cut.as_str()));
+    }
+}
@@ -0,0 +1,143 @@
+use std::fmt;
+use crate::owm::model::MinuteForecast;
+
+/// Precipitation in mm/h below which a minute counts as dry
+pub const WET_THRESHOLD: f32 = 0.1;
+
+/// Short term precipitation outlook from the minutely forecast
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Nowcast {
+    /// Dry for the whole forecast
+    Dry,
+    /// Dry now, precipitation later. `lasting` is `None` when it doesn't stop
+    /// before the end of the forecast.
+    Starting { in_minutes: u32, lasting: Option<u32> },
+    /// Precipitating now and dry later
+    Stopping { in_minutes: u32 },
+    /// Precipitating for the whole forecast
+    Continuing,
+}
+
+impl Nowcast {
+    /// Analyses the minutes of `series` from `now` onwards
+    pub fn from_minutes(series: &[MinuteForecast], now: u64) -> Self {
+        let wet: Vec<bool> = series.iter()
+            // Minute in progress included
+            .filter(|minute| minute.dt + 60 > now)
+            .map(|minute| minute.precipitation >= WET_THRESHOLD)
+            .collect();
+
+        let Some(&wet_now) = wet.first() else {
+            return Nowcast::Dry;
+        };
+        let change = |from: usize, to: bool| wet[from..].iter().position(|wet| *wet == to).map(|index| from + index);
+
+        if wet_now {
+            match change(0, false) {
+                Some(stop) => Nowcast::Stopping { in_minutes: stop as u32 },
+                None => Nowcast::Continuing,
+            }
+        } else {
+            match change(0, true) {
+                Some(start) => Nowcast::Starting {
+                    in_minutes: start as u32,
+                    lasting: change(start, false).map(|stop| (stop - start) as u32),
+                },
+                None => Nowcast::Dry,
+            }
+        }
+    }
+}
+
+impl fmt::Display for Nowcast {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Nowcast::Dry => write!(f, "No rain for the next hour"),
+            Nowcast::Starting { in_minutes, lasting: Some(lasting) } => {
+                write!(f, "Rain starting in {} min, lasting ~{} min", in_minutes, lasting)
+            }
+            Nowcast::Starting { in_minutes, lasting: None } => write!(f, "Rain starting in {} min", in_minutes),
+            Nowcast::Stopping { in_minutes } => write!(f, "Rain stopping in {} min", in_minutes),
+            Nowcast::Continuing => write!(f, "Rain for the next hour"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const START: u64 = 1_700_000_000;
+
+    /// One minute per value from `START`
+    fn series(precipitation: &[f32]) -> Vec<MinuteForecast> {
+        precipitation.iter()
+            .enumerate()
+            .map(|(minute, precipitation)| MinuteForecast { dt: START + minute as u64 * 60, precipitation: *precipitation })
+            .collect()
+    }
+
+    /// `wet` minutes of rain after `dry` dry ones, over a full hour
+    fn hour(dry: usize, wet: usize) -> Vec<MinuteForecast> {
+        let mut precipitation = vec![0.0; 61];
+        precipitation[dry..(dry + wet).min(61)].fill(1.2);
+        series(&precipitation)
+    }
+
+    #[test]
+    fn empty_series_is_dry() {
+        assert_eq!(Nowcast::from_minutes(&[], START), Nowcast::Dry);
+    }
+
+    #[test]
+    fn all_zero_series_is_dry() {
+        assert_eq!(Nowcast::from_minutes(&series(&[0.0; 61]), START), Nowcast::Dry);
+        assert_eq!(Nowcast::Dry.to_string(), "No rain for the next hour");
+    }
+
+    #[test]
+    fn drizzle_below_threshold_is_dry() {
+        assert_eq!(Nowcast::from_minutes(&series(&[0.09; 61]), START), Nowcast::Dry);
+        assert_eq!(Nowcast::from_minutes(&series(&[WET_THRESHOLD; 61]), START), Nowcast::Continuing);
+    }
+
+    #[test]
+    fn rain_starting() {
+        assert_eq!(
+            Nowcast::from_minutes(&hour(12, 20), START),
+            Nowcast::Starting { in_minutes: 12, lasting: Some(20) },
+        );
+        assert_eq!(Nowcast::from_minutes(&hour(40, 60), START), Nowcast::Starting { in_minutes: 40, lasting: None });
+        assert_eq!(
+            Nowcast::Starting { in_minutes: 12, lasting: Some(20) }.to_string(),
+            "Rain starting in 12 min, lasting ~20 min",
+        );
+    }
+
+    #[test]
+    fn rain_stopping_and_continuing() {
+        assert_eq!(Nowcast::from_minutes(&hour(0, 25), START), Nowcast::Stopping { in_minutes: 25 });
+        assert_eq!(Nowcast::from_minutes(&hour(0, 61), START), Nowcast::Continuing);
+        assert_eq!(Nowcast::Stopping { in_minutes: 25 }.to_string(), "Rain stopping in 25 min");
+    }
+
+    #[test]
+    fn past_minutes_are_skipped() {
+        let series = hour(12, 20);
+        // Rain started 3 minutes ago
+        assert_eq!(Nowcast::from_minutes(&series, START + 15 * 60), Nowcast::Stopping { in_minutes: 17 });
+        // The minute in progress counts as now
+        assert_eq!(Nowcast::from_minutes(&series, START + 11 * 60 + 59), Nowcast::Starting { in_minutes: 1, lasting: Some(20) });
+        // The forecast is over
+        assert_eq!(Nowcast::from_minutes(&series, START + 61 * 60), Nowcast::Dry);
+    }
+
+    #[test]
+    fn partial_hour() {
+        // Only the first 20 minutes were returned
+        let series = series(&[0.0, 0.0, 0.0, 0.0, 0.0, 0.8, 0.8, 0.8, 0.8, 0.8, 0.8, 0.8, 0.8, 0.8, 0.8, 0.8, 0.8, 0.8, 0.8, 0.8]);
+        assert_eq!(Nowcast::from_minutes(&series, START), Nowcast::Starting { in_minutes: 5, lasting: None });
+        assert_eq!(Nowcast::from_minutes(&series, START + 10 * 60), Nowcast::Continuing);
+        assert_eq!(Nowcast::from_minutes(&series[..5], START), Nowcast::Dry);
+    }
+}
@@ -0,0 +1,311 @@
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use anyhow::{bail, Error, Result};
+
+/// Screens the station can show
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Page {
+    Overview,
+    /// 48 hour forecast
+    Hourly,
+    /// 8 day forecast
+    Daily,
+    Alerts,
+    /// Sun and moon. Air quality would need the separate air pollution API,
+    /// which isn't fetched.
+    Astronomy,
+    /// Current conditions of every location
+    Comparison,
+}
+
+impl Page {
+    pub const ALL: [Page; 6] = [Page::Overview, Page::Hourly, Page::Daily, Page::Alerts, Page::Astronomy, Page::Comparison];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Page::Overview => "overview",
+            Page::Hourly => "hourly",
+            Page::Daily => "daily",
+            Page::Alerts => "alerts",
+            Page::Astronomy => "astronomy",
+            Page::Comparison => "comparison",
+        }
+    }
+}
+
+impl FromStr for Page {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<Self> {
+        match Page::ALL.iter().find(|page| page.name() == name) {
+            Some(page) => Ok(*page),
+            None => bail!("Unknown page '{}'", name),
+        }
+    }
+}
+
+/// Source of time for the scheduler, so it can be driven by a fake clock
+pub trait Clock {
+    /// Time elapsed since an arbitrary fixed point
+    fn now(&self) -> Duration;
+}
+
+pub struct SystemClock {
+    start: Instant,
+}
+
+impl SystemClock {
+    pub fn new() -> Self {
+        SystemClock { start: Instant::now() }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        SystemClock::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+/// What the scheduler knows about the data being shown
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct PageContext {
+    pub has_alerts: bool,
+}
+
+/// Picks a page regardless of the rotation, e.g. when something needs attention
+pub trait PageRule {
+    fn select(&self, context: &PageContext) -> Option<Page>;
+}
+
+/// Shows the alerts page for as long as there are active alerts
+pub struct AlertTakeover;
+
+impl PageRule for AlertTakeover {
+    fn select(&self, context: &PageContext) -> Option<Page> {
+        context.has_alerts.then_some(Page::Alerts)
+    }
+}
+
+/// Rotates through pages, each shown for a fixed time, unless a rule takes over.
+pub struct PageScheduler<C> {
+    clock: C,
+    rotation: Vec<(Page, Duration)>,
+    rules: Vec<Box<dyn PageRule>>,
+    current: usize,
+    shown_since: Duration,
+    cycles: u64,
+}
+
+#[allow(dead_code)]
+impl<C: Clock> PageScheduler<C> {
+    pub fn new(clock: C, rotation: Vec<(Page, Duration)>) -> Result<Self> {
+        if rotation.is_empty() {
+            bail!("Page rotation is empty");
+        }
+        if let Some((page, _)) = rotation.iter().find(|(_, duration)| duration.is_zero()) {
+            bail!("Page '{}' has no display time", page.name());
+        }
+        let shown_since = clock.now();
+
+        Ok(PageScheduler {
+            clock,
+            rotation,
+            rules: Vec::new(),
+            current: 0,
+            shown_since,
+            cycles: 0,
+        })
+    }
+
+    /// Rules are checked in the order they were added, before the rotation
+    pub fn with_rule(mut self, rule: impl PageRule + 'static) -> Self {
+        self.rules.push(Box::new(rule));
+        self
+    }
+
+    /// Advances the rotation past every page that has been shown long enough.
+    /// The rotation keeps running while a rule takes over.
+    pub fn update(&mut self) {
+        let now = self.clock.now();
+        // Catch up on every page that expired, e.g. after a long refresh
+        loop {
+            let (_, duration) = self.rotation[self.current];
+            if now.saturating_sub(self.shown_since) < duration {
+                break;
+            }
+            self.shown_since += duration;
+            self.next();
+        }
+    }
+
+    /// Page to show, the first rule that applies or else the current page of the rotation
+    pub fn page(&self, context: &PageContext) -> Page {
+        self.rules.iter()
+            .find_map(|rule| rule.select(context))
+            .unwrap_or(self.rotation[self.current].0)
+    }
+
+    /// Skips to the next page of the rotation immediately
+    pub fn advance(&mut self) -> Page {
+        self.next();
+        self.shown_since = self.clock.now();
+        self.rotation[self.current].0
+    }
+
+    /// Number of times the whole rotation was shown
+    pub fn cycles(&self) -> u64 {
+        self.cycles
+    }
+
+    /// Time until the rotation moves on from the current page
+    pub fn remaining(&self) -> Duration {
+        let (_, duration) = self.rotation[self.current];
+        duration.saturating_sub(self.clock.now().saturating_sub(self.shown_since))
+    }
+
+    fn next(&mut self) {
+        self.current = (self.current + 1) % self.rotation.len();
+        if self.current == 0 {
+            self.cycles += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use super::*;
+
+    #[derive(Clone, Default)]
+    struct FakeClock(Rc<Cell<Duration>>);
+
+    impl FakeClock {
+        fn set(&self, secs: u64) {
+            self.0.set(Duration::from_secs(secs));
+        }
+    }
+
+    impl Clock for FakeClock {
+        fn now(&self) -> Duration {
+            self.0.get()
+        }
+    }
+
+    const NO_ALERTS: PageContext = PageContext { has_alerts: false };
+    const ALERTS: PageContext = PageContext { has_alerts: true };
+
+    fn scheduler(clock: &FakeClock) -> PageScheduler<FakeClock> {
+        PageScheduler::new(clock.clone(), vec![
+            (Page::Overview, Duration::from_secs(60)),
+            (Page::Hourly, Duration::from_secs(30)),
+            (Page::Daily, Duration::from_secs(10)),
+        ]).unwrap()
+    }
+
+    fn page_at(scheduler: &mut PageScheduler<FakeClock>, clock: &FakeClock, secs: u64) -> Page {
+        clock.set(secs);
+        scheduler.update();
+        scheduler.page(&NO_ALERTS)
+    }
+
+    #[test]
+    fn rejects_invalid_rotations() {
+        assert!(PageScheduler::new(FakeClock::default(), vec![]).is_err());
+        let rotation = vec![(Page::Overview, Duration::from_secs(60)), (Page::Daily, Duration::ZERO)];
+        assert!(PageScheduler::new(FakeClock::default(), rotation).is_err());
+    }
+
+    #[test]
+    fn rotates_through_pages() {
+        let clock = FakeClock::default();
+        let mut scheduler = scheduler(&clock);
+
+        assert_eq!(page_at(&mut scheduler, &clock, 0), Page::Overview);
+        assert_eq!(page_at(&mut scheduler, &clock, 59), Page::Overview);
+        assert_eq!(page_at(&mut scheduler, &clock, 60), Page::Hourly);
+        assert_eq!(page_at(&mut scheduler, &clock, 89), Page::Hourly);
+        assert_eq!(page_at(&mut scheduler, &clock, 90), Page::Daily);
+        assert_eq!(scheduler.cycles(), 0);
+        assert_eq!(page_at(&mut scheduler, &clock, 100), Page::Overview);
+        assert_eq!(scheduler.cycles(), 1);
+    }
+
+    #[test]
+    fn catches_up_on_expired_pages() {
+        let clock = FakeClock::default();
+        let mut scheduler = scheduler(&clock);
+
+        // Two full rotations and 65s into the third
+        assert_eq!(page_at(&mut scheduler, &clock, 2 * 100 + 65), Page::Hourly);
+        assert_eq!(scheduler.cycles(), 2);
+        // Page times stay aligned to the rotation, not to the late update
+        assert_eq!(scheduler.remaining(), Duration::from_secs(25));
+    }
+
+    #[test]
+    fn remaining_counts_down() {
+        let clock = FakeClock::default();
+        let mut scheduler = scheduler(&clock);
+
+        assert_eq!(scheduler.remaining(), Duration::from_secs(60));
+        clock.set(45);
+        assert_eq!(scheduler.remaining(), Duration::from_secs(15));
+        // Expired but not updated yet
+        clock.set(70);
+        assert_eq!(scheduler.remaining(), Duration::ZERO);
+        scheduler.update();
+        assert_eq!(scheduler.remaining(), Duration::from_secs(20));
+    }
+
+    #[test]
+    fn advance_restarts_the_page_time() {
+        let clock = FakeClock::default();
+        let mut scheduler = scheduler(&clock);
+
+        clock.set(50);
+        assert_eq!(scheduler.advance(), Page::Hourly);
+        assert_eq!(scheduler.remaining(), Duration::from_secs(30));
+        assert_eq!(page_at(&mut scheduler, &clock, 79), Page::Hourly);
+        assert_eq!(page_at(&mut scheduler, &clock, 80), Page::Daily);
+
+        assert_eq!(scheduler.advance(), Page::Overview);
+        assert_eq!(scheduler.cycles(), 1);
+    }
+
+    #[test]
+    fn alerts_take_over_without_stopping_the_rotation() {
+        let clock = FakeClock::default();
+        let mut scheduler = scheduler(&clock).with_rule(AlertTakeover);
+
+        assert_eq!(scheduler.page(&ALERTS), Page::Alerts);
+        clock.set(100);
+        scheduler.update();
+        assert_eq!(scheduler.page(&ALERTS), Page::Alerts);
+        assert_eq!(scheduler.cycles(), 1);
+        // Back to where the rotation got to once the alerts are over
+        assert_eq!(scheduler.page(&NO_ALERTS), Page::Overview);
+    }
+
+    #[test]
+    fn rules_apply_in_order() {
+        struct Always(Page);
+
+        impl PageRule for Always {
+            fn select(&self, _: &PageContext) -> Option<Page> {
+                Some(self.0)
+            }
+        }
+
+        let clock = FakeClock::default();
+        let scheduler = scheduler(&clock).with_rule(AlertTakeover).with_rule(Always(Page::Comparison));
+        assert_eq!(scheduler.page(&ALERTS), Page::Alerts);
+        assert_eq!(scheduler.page(&NO_ALERTS), Page::Comparison);
+    }
+}
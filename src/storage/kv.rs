@@ -0,0 +1,8 @@
+use anyhow::Result;
+
+/// String settings by key, so code caching in NVS can run against a map on the host
+pub trait KeyValue {
+    fn get_string(&self, key: &str) -> Result<Option<String>>;
+
+    fn set_string(&mut self, key: &str, value: &str) -> Result<()>;
+}
@@ -0,0 +1,60 @@
+use std::ffi::CString;
+use anyhow::Result;
+use esp_idf_svc::nvs::{EspDefaultNvsPartition, EspNvs, NvsDefault};
+use esp_idf_sys::{esp, nvs_close, nvs_commit, nvs_erase_all, nvs_handle_t, nvs_open, nvs_open_mode_t_NVS_READWRITE};
+
+mod kv;
+
+pub use kv::KeyValue;
+
+/// NVS namespace holding the station settings
+const NAMESPACE: &str = "weather";
+
+/// Settings persisted in NVS. Keys are limited to 15 characters.
+pub struct Storage {
+    nvs: EspNvs<NvsDefault>,
+}
+
+#[allow(dead_code)]
+impl Storage {
+    pub fn new(partition: EspDefaultNvsPartition) -> Result<Self> {
+        Ok(Storage {
+            nvs: EspNvs::new(partition, NAMESPACE, true)?,
+        })
+    }
+
+    pub fn remove(&mut self, key: &str) -> Result<bool> {
+        Ok(self.nvs.remove(key)?)
+    }
+}
+
+impl KeyValue for Storage {
+    fn get_string(&self, key: &str) -> Result<Option<String>> {
+        let Some(len) = self.nvs.len(key)? else {
+            return Ok(None);
+        };
+
+        let mut buf = vec![0_u8; len];
+        self.nvs.get_raw(key, &mut buf)?;
+        Ok(Some(String::from_utf8(buf)?))
+    }
+
+    fn set_string(&mut self, key: &str, value: &str) -> Result<()> {
+        self.nvs.set_raw(key, value.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Erases every station setting. Only the station namespace is erased, the WiFi
+/// driver state and PHY calibration in other namespaces are kept.
+pub fn erase_all() -> Result<()> {
+    let namespace = CString::new(NAMESPACE)?;
+    let mut handle: nvs_handle_t = 0;
+    esp!(unsafe { nvs_open(namespace.as_ptr(), nvs_open_mode_t_NVS_READWRITE, &mut handle) })?;
+
+    let erased = esp!(unsafe { nvs_erase_all(handle) })
+        .and_then(|_| esp!(unsafe { nvs_commit(handle) }));
+    unsafe { nvs_close(handle) };
+    erased?;
+    Ok(())
+}
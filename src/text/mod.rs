@@ -0,0 +1,249 @@
+//! Line breaking for u8g2 fonts, which only render single lines.
+
+use std::fmt::Debug;
+use anyhow::{anyhow, bail, Result};
+use embedded_graphics::{
+    geometry::*,
+    pixelcolor::BinaryColor,
+    prelude::*,
+};
+use u8g2_fonts::{
+    FontRenderer,
+    types::*,
+};
+
+const ELLIPSIS: &str = "...";
+
+/// Lines of text broken to fit a width, in the font they were measured with
+pub struct Paragraph<'a> {
+    pub font: &'a FontRenderer,
+    pub lines: Vec<String>,
+    pub line_height: u32,
+}
+
+impl Paragraph<'_> {
+    pub fn draw<D>(&self, top_left: Point, target: &mut D) -> Result<()>
+        where
+            D: DrawTarget<Color = BinaryColor>,
+            D::Error: Debug,
+    {
+        for (index, line) in self.lines.iter().enumerate() {
+            self.font.render(
+                line.as_str(),
+                top_left + Point::new(0, (self.line_height * index as u32) as i32),
+                VerticalPosition::Top,
+                FontColor::Transparent(BinaryColor::On),
+                target,
+            ).map_err(|err| anyhow!("Drawing text failed: {:?}", err))?;
+        }
+        Ok(())
+    }
+}
+
+/// Width in pixels of `text` rendered on a single line
+pub fn text_width(font: &FontRenderer, text: &str) -> Result<u32> {
+    let dimensions = font.get_rendered_dimensions(text, Point::zero(), VerticalPosition::Top)
+        .map_err(|err| anyhow!("Measuring text failed: {:?}", err))?;
+    Ok(dimensions.bounding_box.map_or(0, |bounds| (bounds.top_left.x.max(0) as u32) + bounds.size.width))
+}
+
+/// Breaks `text` into lines no wider than `width`, between words when possible.
+/// Words too long for a line are split between characters.
+pub fn wrap(font: &FontRenderer, text: &str, width: u32) -> Result<Vec<String>> {
+    let mut lines = Vec::new();
+
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let candidate = match line.is_empty() {
+                true => word.to_string(),
+                false => format!("{} {}", line, word),
+            };
+            if text_width(font, &candidate)? <= width {
+                line = candidate;
+                continue;
+            }
+
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+            // The word alone may still be too long
+            let mut rest = word;
+            while text_width(font, rest)? > width {
+                let split = longest_prefix(font, rest, width)?;
+                lines.push(rest[..split].to_string());
+                rest = &rest[split..];
+            }
+            line = rest.to_string();
+        }
+        if !line.is_empty() {
+            lines.push(line);
+        }
+    }
+
+    Ok(lines)
+}
+
+/// `text` cut with an ellipsis so it fits in `width`, unchanged if it already fits
+pub fn ellipsize(font: &FontRenderer, text: &str, width: u32) -> Result<String> {
+    if text_width(font, text)? <= width {
+        return Ok(text.to_string());
+    }
+    cut_with_ellipsis(font, text, width)
+}
+
+/// Longest start of `text` that fits in `width` followed by an ellipsis
+fn cut_with_ellipsis(font: &FontRenderer, text: &str, width: u32) -> Result<String> {
+    let mut end = text.len();
+    loop {
+        let candidate = format!("{}{}", text[..end].trim_end(), ELLIPSIS);
+        if end == 0 || text_width(font, &candidate)? <= width {
+            return Ok(candidate);
+        }
+        end = floor_char_boundary(text, end - 1);
+    }
+}
+
+/// Wraps `text` in the first of `fonts` it fits in entirely, the fonts being
+/// ordered from the preferred one. When it fits in none of them, the last font
+/// is used and the text is cut with an ellipsis.
+pub fn fit<'a>(text: &str, fonts: &[&'a FontRenderer], area: Size, line_spacing: u32) -> Result<Paragraph<'a>> {
+    for (index, font) in fonts.iter().copied().enumerate() {
+        let line_height = font.get_default_line_height() + line_spacing;
+        let max_lines = (area.height / line_height.max(1)) as usize;
+        let mut lines = wrap(font, text, area.width)?;
+
+        if lines.len() <= max_lines {
+            return Ok(Paragraph { font, lines, line_height });
+        }
+        if index + 1 == fonts.len() {
+            lines.truncate(max_lines);
+            if let Some(line) = lines.last_mut() {
+                // Always mark the cut, even when the last kept line fits
+                *line = cut_with_ellipsis(font, line, area.width)?;
+            }
+            return Ok(Paragraph { font, lines, line_height });
+        }
+    }
+
+    bail!("No font to fit text with")
+}
+
+fn longest_prefix(font: &FontRenderer, text: &str, width: u32) -> Result<usize> {
+    let mut end = 0;
+    for (index, char) in text.char_indices() {
+        let next = index + char.len_utf8();
+        if text_width(font, &text[..next])? > width {
+            break;
+        }
+        end = next;
+    }
+    // Always make progress, even if a single character doesn't fit
+    Ok(match end {
+        0 => text.chars().next().map_or(0, char::len_utf8),
+        end => end,
+    })
+}
+
+fn floor_char_boundary(text: &str, mut index: usize) -> usize {
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use u8g2_fonts::fonts;
+
+    // ProFont is monospaced, 9px per character at 17 and 12px at 22
+    fn small() -> FontRenderer {
+        FontRenderer::new::<fonts::u8g2_font_profont17_tf>()
+    }
+
+    fn large() -> FontRenderer {
+        FontRenderer::new::<fonts::u8g2_font_profont22_tf>()
+    }
+
+    #[test]
+    fn words_wrap_at_the_width() {
+        let font = small();
+        let width = text_width(&font, "the quick").unwrap();
+
+        let lines = wrap(&font, "the quick brown fox jumps over", width).unwrap();
+        assert_eq!(lines, ["the quick", "brown fox", "jumps", "over"]);
+        for line in &lines {
+            assert!(text_width(&font, line).unwrap() <= width, "{}", line);
+        }
+        assert_eq!(wrap(&font, "the\nquick  brown", width).unwrap(), ["the", "quick", "brown"]);
+        assert!(wrap(&font, "", width).unwrap().is_empty());
+    }
+
+    #[test]
+    fn long_words_are_split() {
+        let font = small();
+        let width = text_width(&font, "abcd").unwrap();
+
+        assert_eq!(wrap(&font, "abcdefghij", width).unwrap(), ["abcd", "efgh", "ij"]);
+        assert_eq!(wrap(&font, "go abcdefghij ok", width).unwrap(), ["go", "abcd", "efgh", "ij", "ok"]);
+        // A character wider than the line still gets one
+        assert_eq!(wrap(&font, "abc", 0).unwrap(), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn overflow_is_ellipsized() {
+        let font = small();
+
+        assert_eq!(ellipsize(&font, "Thunderstorm", text_width(&font, "Thunderstorm").unwrap()).unwrap(), "Thunderstorm");
+        assert_eq!(ellipsize(&font, "Thunderstorm", text_width(&font, "Thun...").unwrap()).unwrap(), "Thun...");
+        // No space left before the ellipsis
+        assert_eq!(ellipsize(&font, "Heavy rain", text_width(&font, "Heavy ...").unwrap()).unwrap(), "Heavy...");
+        assert_eq!(ellipsize(&font, "Thunderstorm", 0).unwrap(), "...");
+    }
+
+    #[test]
+    fn multibyte_characters_are_cut_whole() {
+        let font = small();
+
+        assert_eq!(ellipsize(&font, "Züüüüü", text_width(&font, "Zü...").unwrap()).unwrap(), "Zü...");
+        assert_eq!(ellipsize(&font, "Zürich", text_width(&font, "Zür...").unwrap()).unwrap(), "Zür...");
+        assert_eq!(wrap(&font, "äöüäöü", text_width(&font, "äöü").unwrap()).unwrap(), ["äöü", "äöü"]);
+        assert_eq!(wrap(&font, "äöü", 0).unwrap(), ["ä", "ö", "ü"]);
+    }
+
+    #[test]
+    fn fit_falls_back_to_smaller_fonts() {
+        let (large, small) = (large(), small());
+        let fonts = [&large, &small];
+        let text = "Light rain and snow showers";
+
+        let paragraph = fit(text, &fonts, Size::new(200, 46), 0).unwrap();
+        assert!(std::ptr::eq(paragraph.font, &large));
+        assert_eq!(paragraph.lines, ["Light rain and", "snow showers"]);
+        assert_eq!(paragraph.line_height, 23);
+
+        let paragraph = fit(text, &fonts, Size::new(200, 40), 0).unwrap();
+        assert!(std::ptr::eq(paragraph.font, &small));
+        assert_eq!(paragraph.lines, ["Light rain and snow", "showers"]);
+        assert_eq!(paragraph.line_height, 18);
+
+        // Line spacing counts towards the height
+        let paragraph = fit(text, &fonts, Size::new(200, 46), 2).unwrap();
+        assert!(std::ptr::eq(paragraph.font, &small));
+        assert_eq!(paragraph.line_height, 20);
+    }
+
+    #[test]
+    fn fit_ellipsizes_in_the_last_font() {
+        let (large, small) = (large(), small());
+
+        let paragraph = fit("Light rain and snow showers", &[&large, &small], Size::new(200, 20), 0).unwrap();
+        assert!(std::ptr::eq(paragraph.font, &small));
+        assert_eq!(paragraph.lines, ["Light rain and snow..."]);
+
+        let paragraph = fit("Light rain and snow showers", &[&small], Size::new(200, 10), 0).unwrap();
+        assert!(paragraph.lines.is_empty());
+        assert!(fit("Rain", &[], Size::new(200, 20), 0).is_err());
+    }
+}
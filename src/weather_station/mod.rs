@@ -1,11 +1,10 @@
-use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use anyhow::Result;
+use log::*;
 use embedded_graphics::{
     primitives::*,
     geometry::*,
     prelude::*,
-    image::*
 };
 use embedded_graphics::pixelcolor::BinaryColor;
 use u8g2_fonts::{
@@ -15,220 +14,580 @@ use u8g2_fonts::{
 };
 use crate::config::CONFIG;
 use crate::display::display::{Display, DISPLAY_HEIGHT, DISPLAY_WIDTH};
-use crate::icons::{
-    WeatherIconSet,
-};
+use crate::display::error::DisplayError;
+use crate::http_client::HttpClient;
+use crate::input::{Action, ButtonMapping, button::Button};
+use crate::layout::{description, Layout, Node};
+use crate::locations::{parse_locations, FetchScheduler, Location};
+use crate::ota;
+use crate::pages::{AlertTakeover, Clock, Page, PageContext, PageScheduler, SystemClock};
+use crate::icons::theme::{IconTheme, Theme};
 use crate::owm::{
     api::fetch_owm_report,
-    model::{CurrentWeather,DailyForecast, HourlyForecast, WeatherData},
-    icons::get_icon_for_current_weather,
+    geo,
+    model::WeatherData,
 };
-use crate::owm::icons::get_icon_for_daily_forecast;
+use crate::storage::{self, KeyValue, Storage};
+use crate::widgets::{LocationWeather, Screen, ViewModel, WidgetRegistry};
 
 const MARGIN: u32 = 8;
-// Seems like icons have a bunch of padding on the horizontal axis
-// This is a dirty attempt to gain some screen space
-// Padding is actually closer to 32, but this is enough to make things fit
-const IMG_ICON_PADDING: u32 = 16;
+// Consecutive display failures tolerated before restarting the whole station
+const MAX_DISPLAY_FAILURES: u32 = 3;
+// NVS key of a custom layout description, overrides the configured preset.
+// Written from `layout_url` at boot, erased by a factory reset.
+const LAYOUT_KEY: &str = "layout";
+// Largest layout description accepted from `layout_url`
+const MAX_LAYOUT_SIZE: usize = 8 * 1024;
+// NVS key of the icon theme name, overrides the configured one.
+// Written by the `next_theme` button action, erased by a factory reset.
+const ICON_THEME_KEY: &str = "icon_theme";
+// How long the diagnostics stay on screen before going back to the pages
+const DIAGNOSTICS_DURATION: Duration = Duration::from_secs(60);
+
+struct PageScreen {
+    page: Page,
+    layout: Layout,
+    screen: Screen<Display>,
+}
 
 pub struct WeatherStation {
     display: Display,
-    rect: DisplayRect,
-}
-
-pub struct DisplayRect {
-    pub viewport: Rectangle,
-    pub current_weather: Rectangle,
-    pub weather_icon: Rectangle,
-    pub current_temp: Rectangle,
-    pub feels_like: Rectangle,
-    pub current_temp_unit: Rectangle,
-    pub date_location: Rectangle,
-    pub forecast: Rectangle,
-    pub forecasts: Vec<Rectangle>,
-    pub metrics: Rectangle,
-    pub chart: Rectangle,
+    pages: Vec<PageScreen>,
+    scheduler: PageScheduler<SystemClock>,
+    /// Page on screen, `None` until the first update
+    current: Option<Page>,
+    /// Location on screen, index in `locations`
+    current_location: usize,
+    locations: Vec<Location>,
+    /// Latest report of each location
+    reports: Vec<Option<WeatherData>>,
+    fetcher: FetchScheduler,
+    clock: SystemClock,
+    icon_theme: Box<dyn IconTheme>,
+    storage: Storage,
+    button: Button,
+    mapping: ButtonMapping,
+    /// Set while the diagnostics replace the page
+    diagnostics_until: Option<Instant>,
+    force_fetch: bool,
+    /// Last check for a firmware update, see `pages::Clock`
+    ota_checked_at: Option<Duration>,
+    /// Display failures since the last successful update
+    display_failures: u32,
 }
 
 impl WeatherStation {
-    pub fn new(display: Display) -> Self {
+    pub fn new(display: Display, mut storage: Storage, button: Button) -> Result<Self> {
+        let app_config = CONFIG;
+        let widgets = WidgetRegistry::with_defaults();
+
+        Self::provision_layout(&mut storage, &widgets);
+        let (layout, screen) = Self::load_layout(&storage, &widgets)?;
+        let mut pages = vec![PageScreen { page: Page::Overview, layout, screen }];
+        for page in Page::ALL.into_iter().filter(|page| *page != Page::Overview) {
+            match description::preset(page.name()).and_then(|node| Self::build_screen(node, &widgets)) {
+                Ok((layout, screen)) => pages.push(PageScreen { page, layout, screen }),
+                Err(err) => warn!("Page '{}' disabled: {:#}", page.name(), err),
+            }
+        }
 
-        let viewport_size = Size::new(DISPLAY_WIDTH - MARGIN, DISPLAY_HEIGHT - MARGIN);
-        let current_icon_size = Size::new(196 - IMG_ICON_PADDING, 196);
-        let current_temp_size = Size::new(196, current_icon_size.height);
+        let page_duration = Duration::from_secs(app_config.page_duration);
+        let rotation = app_config.pages.split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .filter_map(|name| match name.parse::<Page>() {
+                Ok(page) if pages.iter().any(|screen| screen.page == page) => Some((page, page_duration)),
+                Ok(_) => None,
+                Err(err) => {
+                    warn!("Ignoring page in rotation: {}", err);
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+        let rotation = match rotation.is_empty() {
+            true => vec![(Page::Overview, page_duration)],
+            false => rotation,
+        };
 
-        let forecast_separator_size = Size::new(IMG_ICON_PADDING, current_icon_size.height);
+        let mut scheduler = PageScheduler::new(SystemClock::new(), rotation)?;
+        if pages.iter().any(|screen| screen.page == Page::Alerts) {
+            scheduler = scheduler.with_rule(AlertTakeover);
+        }
 
-        let current_weather_size = Size::new(current_temp_size.width + current_icon_size.width + forecast_separator_size.width, current_icon_size.height);
+        let locations = Self::load_locations(&mut storage);
+        info!("Locations: {:?}", locations.iter().map(|location| &location.name).collect::<Vec<_>>());
+        let fetcher = FetchScheduler::new(&locations, app_config.api_daily_budget);
 
-        let temp_unit_size = Size::new(32, current_temp_size.height);
-        let temp_feels_like_size = Size::new(current_temp_size.width, 32);
+        Ok(WeatherStation {
+            display,
+            pages,
+            scheduler,
+            current: None,
+            current_location: 0,
+            reports: locations.iter().map(|_| None).collect(),
+            locations,
+            fetcher,
+            clock: SystemClock::new(),
+            icon_theme: Self::load_icon_theme(&storage),
+            storage,
+            button,
+            mapping: Self::load_button_mapping(),
+            diagnostics_until: None,
+            force_fetch: false,
+            ota_checked_at: None,
+            display_failures: 0,
+        })
+    }
 
-        let date_location_size = Size::new(viewport_size.width - current_weather_size.width, 64);
-        let forecast_size = Size::new(viewport_size.width - current_weather_size.width, current_weather_size.height - date_location_size.height);
+    fn build_screen(node: Node, widgets: &WidgetRegistry<Display>) -> Result<(Layout, Screen<Display>)> {
+        let area = Rectangle::new(Point::zero(), Size::new(DISPLAY_WIDTH, DISPLAY_HEIGHT));
+        let layout = node.compute(area)?;
+        layout.validate()?;
+        let screen = widgets.build(&layout)?;
+        Ok((layout, screen))
+    }
 
-        let metrics_size = Size::new(294, viewport_size.height - current_weather_size.height);
-        let chart_size = Size::new(viewport_size.width - metrics_size.width, viewport_size.height - current_weather_size.height);
+    /// Stores the layout downloaded from `layout_url` in NVS, once it proved valid.
+    /// Download failures keep the layout stored before, so a custom layout survives
+    /// a server being down.
+    fn provision_layout(storage: &mut Storage, widgets: &WidgetRegistry<Display>) {
+        let app_config = CONFIG;
+        if app_config.layout_url.is_empty() {
+            return;
+        }
 
-        let viewport = Rectangle::new(Point::new(MARGIN as i32, MARGIN as i32), viewport_size);
-        let current_weather = Rectangle::new(viewport.top_left, current_weather_size);
-        let weather_icon = Rectangle::new(current_weather.top_left, current_icon_size);
+        let client = HttpClient::new().with_max_body_size(MAX_LAYOUT_SIZE);
+        let json = match client.get_string(app_config.layout_url) {
+            Ok(json) => json,
+            Err(err) => {
+                warn!("Downloading layout failed: {:#}", err);
+                return;
+            }
+        };
+        if let Err(err) = description::parse(&json).and_then(|node| Self::build_screen(node, widgets)) {
+            warn!("Ignoring downloaded layout: {:#}", err);
+            return;
+        }
 
-        let current_temp = Rectangle::new(weather_icon.anchor_point(AnchorPoint::TopRight), current_temp_size);
-        let forecast_separator = Rectangle::new(current_temp.anchor_point(AnchorPoint::TopRight), forecast_separator_size);
+        if storage.get_string(LAYOUT_KEY).is_ok_and(|stored| stored.as_deref() == Some(json.as_str())) {
+            return;
+        }
+        match storage.set_string(LAYOUT_KEY, &json) {
+            Ok(()) => info!("Stored layout downloaded from {}", app_config.layout_url),
+            Err(err) => warn!("Failed to store layout in NVS: {:#}", err),
+        }
+    }
 
-        let current_temp_unit = Rectangle::new(current_temp.anchor_point(AnchorPoint::TopRight), temp_unit_size)
-            .translate(Point::new(-(temp_unit_size.width as i32), 0));
+    /// Loads the overview layout stored in NVS, falling back to the configured
+    /// preset and then to the default one when a layout is missing or invalid.
+    fn load_layout(storage: &Storage, widgets: &WidgetRegistry<Display>) -> Result<(Layout, Screen<Display>)> {
+        let app_config = CONFIG;
+        let build = |node: Node| Self::build_screen(node, widgets);
+
+        match storage.get_string(LAYOUT_KEY) {
+            Ok(Some(json)) => match description::parse(&json).and_then(&build) {
+                Ok(loaded) => {
+                    info!("Using layout stored in NVS");
+                    return Ok(loaded);
+                }
+                Err(err) => warn!("Ignoring layout stored in NVS: {:#}", err),
+            },
+            Ok(None) => {}
+            Err(err) => warn!("Failed to read layout from NVS: {:#}", err),
+        }
 
-        let feels_like = Rectangle::new(current_temp.anchor_point(AnchorPoint::BottomLeft), temp_feels_like_size)
-            .translate(Point::new(0, -(temp_feels_like_size.height as i32)));
+        match description::preset(app_config.layout).and_then(&build) {
+            Ok(loaded) => {
+                info!("Using preset layout '{}'", app_config.layout);
+                return Ok(loaded);
+            }
+            Err(err) => warn!("Ignoring preset layout '{}': {:#}", app_config.layout, err),
+        }
 
-        let date_location = Rectangle::new(forecast_separator.anchor_point(AnchorPoint::TopRight), date_location_size);
+        description::preset(description::DEFAULT_PRESET).and_then(&build)
+    }
 
-        let forecast = Rectangle::new(date_location.anchor_point(AnchorPoint::BottomLeft), forecast_size);
-        let forecasts = [0; 5].iter().enumerate().map(|(i, _)| {
-            let size = Size::new(forecast_size.width / 5, forecast_size.height);
-            let offset_x = size.width * i as u32;
-            Rectangle::new(forecast.anchor_point(AnchorPoint::TopLeft) + Point::new(offset_x as i32, 0), size)
-        }).collect::<Vec<_>>();
+    /// Configured locations, or the single default location
+    fn load_locations(storage: &mut Storage) -> Vec<Location> {
+        let app_config = CONFIG;
+        let fetch_interval = Duration::from_secs(app_config.fetch_interval);
 
-        let metrics = Rectangle::new(current_weather.anchor_point(AnchorPoint::BottomLeft), metrics_size);
-        let chart = Rectangle::new(metrics.anchor_point(AnchorPoint::TopRight), chart_size);
+        match parse_locations(app_config.locations, fetch_interval) {
+            Ok(locations) if !locations.is_empty() => return locations,
+            Ok(_) => {}
+            Err(err) => warn!("Ignoring configured locations: {:#}", err),
+        }
 
-        let rect = DisplayRect {
-            viewport,
-            current_weather,
-            weather_icon,
-            current_temp,
-            feels_like,
-            current_temp_unit,
-            date_location,
-            forecast,
-            forecasts,
-            metrics,
-            chart,
+        let mut location = Location {
+            name: app_config.location_name.to_string(),
+            latitude: app_config.latitude,
+            longitude: app_config.longitude,
+            fetch_interval,
         };
+        if !app_config.location_query.is_empty() {
+            match geo::resolve(storage, app_config.location_query) {
+                Ok(place) => {
+                    location.latitude = place.lat;
+                    location.longitude = place.lon;
+                    location.name = place.display_name();
+                }
+                Err(err) => warn!("Looking up '{}' failed: {:#}", app_config.location_query, err),
+            }
+        }
+        if location.name.is_empty() {
+            location.name = match geo::resolve_coordinates(storage, location.latitude, location.longitude) {
+                Ok(place) => place.display_name(),
+                Err(err) => {
+                    warn!("Naming the location failed: {:#}", err);
+                    format!("{:.2}, {:.2}", location.latitude, location.longitude)
+                }
+            };
+        }
 
-        WeatherStation {
-            display,
-            rect
+        vec![location]
+    }
+
+    /// Icon theme named in NVS, else the configured one, else Weather Icons
+    fn load_icon_theme(storage: &Storage) -> Box<dyn IconTheme> {
+        let app_config = CONFIG;
+        let stored = match storage.get_string(ICON_THEME_KEY) {
+            Ok(name) => name,
+            Err(err) => {
+                warn!("Failed to read icon theme from NVS: {:#}", err);
+                None
+            }
+        };
+        let name = stored.as_deref().unwrap_or(app_config.icon_theme);
+        let theme = match name.parse::<Theme>() {
+            Ok(theme) => theme,
+            Err(err) => {
+                warn!("{}, using {}", err, Theme::WeatherIcons.name());
+                Theme::WeatherIcons
+            }
+        }.build();
+
+        let missing = theme.missing();
+        match missing.is_empty() {
+            true => info!("Icon theme '{}' covers every condition", theme.name()),
+            false => warn!("Icon theme '{}' has no glyph for {:?}", theme.name(), missing),
         }
+        theme
     }
+
+    fn load_button_mapping() -> ButtonMapping {
+        let app_config = CONFIG;
+        let defaults = ButtonMapping::default();
+        let parse = |name: &str, default: Action| match name.parse() {
+            Ok(action) => action,
+            Err(err) => {
+                warn!("{}, using {:?}", err, default);
+                default
+            }
+        };
+
+        ButtonMapping {
+            short: parse(app_config.button_short, defaults.short),
+            double: parse(app_config.button_double, defaults.double),
+            long: parse(app_config.button_long, defaults.long),
+        }
+    }
+
     pub fn run(&mut self) -> Result<()> {
         loop {
-            let weather = fetch_owm_report()?;
+            let fetched = self.fetch_due_reports();
+
+            self.scheduler.update();
+            // Locations take turns, one full page rotation each
+            let location = self.location_to_show();
+            let page = self.scheduler.page(&PageContext { has_alerts: self.has_alerts(location) });
+
+            let showing_diagnostics = self.diagnostics_until
+                .is_some_and(|until| Instant::now() < until);
+            let data_changed = match page {
+                Page::Comparison => !fetched.is_empty(),
+                _ => fetched.contains(&location),
+            };
+            let unchanged = !data_changed && self.current == Some(page) && self.current_location == location;
+            // Nothing to draw unless the data, page or location changed
+            if showing_diagnostics || unchanged || self.reports[location].is_none() {
+                let timeout = self.next_wake_up();
+                if let Err(err) = self.wait_for_input(timeout) {
+                    self.handle_error(err);
+                }
+                continue;
+            }
+            self.current = Some(page);
+            self.current_location = location;
+            self.diagnostics_until = None;
+
+            match self.update() {
+                Ok(()) => self.display_failures = 0,
+                Err(err) => self.handle_error(err),
+            }
+
+            self.check_firmware_update();
+
+            let timeout = self.next_wake_up();
+            if let Err(err) = self.wait_for_input(timeout) {
+                self.handle_error(err);
+            }
+        }
+    }
 
-            self.display.clear(BinaryColor::Off)?;
-            self.draw_weather_report(weather)?;
+    /// Recovers from a display error, restarting the station once the display
+    /// failed `MAX_DISPLAY_FAILURES` times in a row. Other errors, e.g. a widget
+    /// missing data of a partial report, are shown until the next update.
+    fn handle_error(&mut self, err: anyhow::Error) {
+        let Some(display_err) = err.downcast_ref::<DisplayError>() else {
+            error!("Update failed: {:#}", err);
+            if let Err(display_err) = self.show_message("Error", &err.to_string()) {
+                self.handle_error(display_err);
+            }
+            return;
+        };
+        error!("Display update failed: {}", display_err);
 
-            self.display.flush_and_refresh()?;
+        self.display_failures += 1;
+        if self.display_failures >= MAX_DISPLAY_FAILURES {
+            error!("Display failed {} times in a row, restarting", self.display_failures);
+            unsafe { esp_idf_sys::esp_restart() };
+        }
 
-            thread::sleep(Duration::from_secs(60));
+        if let Err(err) = self.recover(display_err) {
+            error!("Display recovery failed: {}", err);
         }
     }
 
-    fn draw_weather_report(&mut self, weather: WeatherData) -> Result<()> {
+    /// Installs a newer firmware and restarts into it, once per check interval
+    fn check_firmware_update(&mut self) {
         let app_config = CONFIG;
-        let location_name = app_config.location_name;
-        let current = weather.current.unwrap();
-        let daily = weather.daily.unwrap();
-        let hourly = weather.hourly.unwrap();
-        let dt = current.dt;
+        let now = self.clock.now();
+        let interval = Duration::from_secs(app_config.ota_check_interval);
+        let due = self.ota_checked_at.map_or(true, |checked_at| now.saturating_sub(checked_at) >= interval);
+        if app_config.ota_manifest_url.is_empty() || !due {
+            return;
+        }
+        self.ota_checked_at = Some(now);
+
+        let manifest = match ota::check(app_config.ota_manifest_url) {
+            Ok(Some(manifest)) => manifest,
+            Ok(None) => return,
+            Err(err) => {
+                warn!("Checking for a firmware update failed: {:#}", err);
+                return;
+            }
+        };
 
-        let large_icon_set = WeatherIconSet::new()?;
-        let small_icon_set = WeatherIconSet::new_small()?;
+        // The update is installed even when the message can't be shown
+        if let Err(err) = self.show_message("Updating firmware", &format!("Installing version {}", manifest.version)) {
+            self.handle_error(err);
+        }
 
-        self.current_weather_icon(&large_icon_set, &current)?;
-        self.current_temperature(&current)?;
-        self.current_feels_like(&current)?;
-        self.current_temp_unit()?;
-        self.date_and_location(dt, location_name)?;
-        self.daily_forecast(&small_icon_set, &daily)?;
+        match ota::install(&manifest) {
+            Ok(()) => unsafe { esp_idf_sys::esp_restart() },
+            Err(err) => {
+                error!("Firmware update failed: {:#}", err);
+                // Redraw the page over the message
+                self.current = None;
+            }
+        }
+    }
 
-        self.debug_draw_rect()?;
+    /// Fetches every location that is due and within the API budget,
+    /// returns the locations that were updated
+    fn fetch_due_reports(&mut self) -> Vec<usize> {
+        if self.force_fetch {
+            self.force_fetch = false;
+            self.fetcher.expire_all();
+        }
 
+        let mut fetched = Vec::new();
+        while let Some(index) = self.fetcher.due(self.clock.now()) {
+            let location = &self.locations[index];
+            info!("Fetching weather report for {}", location.name);
+            let now = self.clock.now();
+            // The previous report stays on screen until a retry succeeds
+            match fetch_owm_report(location) {
+                Ok(report) => {
+                    self.fetcher.fetched(index, now, true);
+                    self.reports[index] = Some(report);
+                    fetched.push(index);
+                }
+                Err(err) => {
+                    warn!("Fetching weather report for {} failed: {:#}", location.name, err);
+                    self.fetcher.fetched(index, now, false);
+                }
+            }
+        }
+        fetched
+    }
 
-        Ok(())
+    fn location_to_show(&self) -> usize {
+        (self.scheduler.cycles() % self.locations.len() as u64) as usize
     }
 
-    fn current_weather_icon(&mut self, icons: &WeatherIconSet, current: &CurrentWeather) -> Result<()> {
-        let icon = get_icon_for_current_weather(icons, current);
-        Image::new(icon, self.rect.current_weather
-            .translate(Point::new(-(IMG_ICON_PADDING as i32) / 2, 0)).top_left)
-            .draw(&mut self.display.color_converted())?;
-        Ok(())
+    fn has_alerts(&self, location: usize) -> bool {
+        self.reports[location].as_ref()
+            .and_then(|report| report.alerts.as_ref())
+            .is_some_and(|alerts| !alerts.is_empty())
+    }
+
+    /// Time until either the page rotates or a report needs updating
+    fn next_wake_up(&mut self) -> Duration {
+        let next_fetch = self.fetcher.next_due(self.clock.now());
+        self.scheduler.remaining()
+            .min(next_fetch)
+            .max(Duration::from_secs(1))
     }
 
-    fn current_temp_unit(&mut self) -> Result<()> {
-        let unit_style = PrimitiveStyleBuilder::new()
-            .stroke_width(4)
-            .stroke_color(BinaryColor::On)
-            .build();
-
-        let circle_diameter: u32 = 12;
-        let circle_center = Point::new(circle_diameter as i32 / 2, circle_diameter as i32 / 2);
-        let offset = Point::new(0, 46); // 46 is half the font size
-        Circle::new(
-            self.rect.current_temp_unit.center() - offset - circle_center,
-            circle_diameter
-        ).into_styled(unit_style)
-            .draw(&mut self.display)?;
+    /// Waits for a button gesture for up to `timeout` and runs its action
+    fn wait_for_input(&mut self, timeout: Duration) -> Result<()> {
+        let Some(gesture) = self.button.wait(timeout) else {
+            return Ok(());
+        };
+
+        match self.mapping.action(gesture) {
+            Action::NextPage => {
+                let page = self.scheduler.advance();
+                info!("Switching to page '{}'", page.name());
+                self.diagnostics_until = None;
+            }
+            Action::ForceFetch => {
+                info!("Forcing weather report update");
+                self.force_fetch = true;
+            }
+            Action::ShowDiagnostics => {
+                self.show_diagnostics()?;
+                self.diagnostics_until = Some(Instant::now() + DIAGNOSTICS_DURATION);
+                // Redraw the page once the diagnostics time out
+                self.current = None;
+            }
+            Action::NextIconTheme => self.next_icon_theme()?,
+            Action::FactoryReset => self.factory_reset()?,
+            Action::None => {}
+        }
 
         Ok(())
     }
 
-    fn current_feels_like(&mut self, current: &CurrentWeather) -> Result<()> {
+    /// Switches to the theme after the current one in `Theme::ALL` and stores it in NVS
+    fn next_icon_theme(&mut self) -> Result<()> {
+        let current = Theme::ALL.iter()
+            .position(|theme| theme.name() == self.icon_theme.name())
+            .unwrap_or(0);
+        let theme = Theme::ALL[(current + 1) % Theme::ALL.len()];
+        info!("Switching to icon theme '{}'", theme.name());
+
+        self.icon_theme = theme.build();
+        // Redraw the page with it
+        self.current = None;
+        self.storage.set_string(ICON_THEME_KEY, theme.name())
+    }
+
+    fn show_diagnostics(&mut self) -> Result<()> {
         let font = FontRenderer::new::<fonts::u8g2_font_profont22_tf>();
+        let uptime = Duration::from_micros(unsafe { esp_idf_sys::esp_timer_get_time() } as u64);
+        let free_heap = unsafe { esp_idf_sys::esp_get_free_heap_size() };
 
-        font.render_aligned(
-            format_args!("Feels Like {}°", current.feels_like.round() as i32),
-            self.rect.feels_like.bounding_box().center(),
-            VerticalPosition::Center,
-            HorizontalAlignment::Center,
-            FontColor::Transparent(BinaryColor::On),
-            &mut self.display,
-        ).unwrap();
+        let temperature = match self.display.temperature() {
+            Ok(temperature) => format!("{}°C", temperature),
+            Err(err) => err.to_string(),
+        };
+        let low_power = match self.display.is_low_power() {
+            Ok(low_power) => low_power.to_string(),
+            Err(err) => err.to_string(),
+        };
+        let lines = [
+            format!("Firmware       {}", env!("CARGO_PKG_VERSION")),
+            format!("Uptime         {}h{:02}", uptime.as_secs() / 3600, uptime.as_secs() % 3600 / 60),
+            format!("Free heap      {} bytes", free_heap),
+            format!("Page           {}", self.current.map_or("none", |page| page.name())),
+            format!("Location       {}", self.locations[self.current_location].name),
+            format!("API budget     {} calls left", self.fetcher.remaining_budget(self.clock.now())),
+            format!("Panel temp     {}", temperature),
+            format!("Panel low pwr  {}", low_power),
+        ];
+
+        self.display.clear(BinaryColor::Off)?;
+        for (index, line) in lines.iter().enumerate() {
+            font.render(
+                line.as_str(),
+                Point::new(MARGIN as i32, (MARGIN + index as u32 * 30) as i32),
+                VerticalPosition::Top,
+                FontColor::Transparent(BinaryColor::On),
+                &mut self.display,
+            ).unwrap();
+        }
+        self.display.flush_and_refresh()?;
+        self.display.sleep()?;
 
         Ok(())
     }
 
-    fn current_temperature(&mut self, current: &CurrentWeather) -> Result<()> {
-        let large_font = FontRenderer::new::<fonts::u8g2_font_logisoso92_tn>();
+    /// Erases the station settings stored in NVS and restarts.
+    /// The display is reinitialised by the restart, its errors don't stop the reset.
+    fn factory_reset(&mut self) -> Result<()> {
+        warn!("Factory reset");
+        if let Err(err) = self.show_message("Factory reset", "Settings erased, restarting") {
+            error!("Showing the factory reset failed: {:#}", err);
+        }
 
-        large_font.render_aligned(
-            format_args!("{}", current.temp.round() as i32),
-            self.rect.current_temp.bounding_box().center(),
-            VerticalPosition::Center,
-            HorizontalAlignment::Center,
-            FontColor::Transparent(BinaryColor::On),
-            &mut self.display,
-        ).unwrap();
+        storage::erase_all()?;
+        unsafe { esp_idf_sys::esp_restart() };
+    }
+
+    /// Layout of the page on screen, the overview before anything was drawn
+    fn page_screen(&self) -> &PageScreen {
+        self.current
+            .and_then(|page| self.pages.iter().find(|screen| screen.page == page))
+            .unwrap_or(&self.pages[0])
+    }
+
+    fn update(&mut self) -> Result<()> {
+        self.display.clear(BinaryColor::Off)?;
+        self.draw_weather_report()?;
 
+        self.display.flush_and_refresh()?;
+        self.display.sleep()?;
         Ok(())
     }
 
-    fn date_and_location(&mut self, current_time: u64, location_name: &str) -> Result<()> {
-        let large = FontRenderer::new::<fonts::u8g2_font_profont29_tf>();
-        let font = FontRenderer::new::<fonts::u8g2_font_profont22_tf>();
+    fn recover(&mut self, cause: &DisplayError) -> Result<()> {
+        self.display.recover()?;
+        self.show_message("Display error", &cause.to_string())
+    }
 
-        let offset_dt = time::OffsetDateTime::from_unix_timestamp(current_time as i64)?;
-        let format = time::format_description::parse("[weekday], [day] [month repr:long] [year]")?;
-        let formatted = offset_dt.format(&format)?;
+    /// Replaces the screen with a message
+    fn show_message(&mut self, title: &str, message: &str) -> Result<()> {
+        self.display.clear(BinaryColor::Off)?;
+        self.draw_error(title, message)?;
+        self.display.flush_and_refresh()?;
+        self.display.sleep()?;
+
+        Ok(())
+    }
+
+    fn draw_error(&mut self, title: &str, message: &str) -> Result<()> {
+        let large = FontRenderer::new::<fonts::u8g2_font_profont29_tf>();
+        let font = FontRenderer::new::<fonts::u8g2_font_profont17_tf>();
+        let center = self.page_screen().layout.get("viewport")
+            .unwrap_or(Rectangle::new(Point::zero(), Size::new(DISPLAY_WIDTH, DISPLAY_HEIGHT)))
+            .center();
 
         large.render_aligned(
-            location_name,
-            self.rect.date_location.anchor_point(AnchorPoint::TopRight),
-            VerticalPosition::Top,
-            HorizontalAlignment::Right,
+            title,
+            center,
+            VerticalPosition::Bottom,
+            HorizontalAlignment::Center,
             FontColor::Transparent(BinaryColor::On),
             &mut self.display,
         ).unwrap();
 
         font.render_aligned(
-            formatted.as_str(),
-            self.rect.date_location.anchor_point(AnchorPoint::TopRight) + Point::new(0, 29),
+            message,
+            center + Point::new(0, MARGIN as i32),
             VerticalPosition::Top,
-            HorizontalAlignment::Right,
+            HorizontalAlignment::Center,
             FontColor::Transparent(BinaryColor::On),
             &mut self.display,
         ).unwrap();
@@ -236,79 +595,29 @@ impl WeatherStation {
         Ok(())
     }
 
-    fn daily_forecast(&mut self, icons: &WeatherIconSet, forecast: &[DailyForecast]) -> Result<()> {
-        for (index, rec) in self.rect.forecasts.iter().enumerate() {
-            let daily = &forecast[index];
-            let icon = get_icon_for_daily_forecast(icons, daily);
-            let img_center_offset = Point::new((icons.WIDTH / 2) as i32, (icons.HEIGHT / 2) as i32);
-
-            Image::new(icon, rec.bounding_box().center() - img_center_offset)
-                .draw(&mut self.display.color_converted())?;
-
-            let txt_offset = Point::new(0, (icons.HEIGHT / 2 + MARGIN) as i32);
-
-            // Draw day of week
-            let font = FontRenderer::new::<fonts::u8g2_font_profont22_tf>();
-            let font_small = FontRenderer::new::<fonts::u8g2_font_profont17_tf>();
-            let offset_dt = time::OffsetDateTime::from_unix_timestamp(daily.dt as i64)?;
-            let format = time::format_description::parse("[weekday repr:short]")?;
-            let day_formatted = offset_dt.format(&format)?;
-
-            font.render_aligned(
-                day_formatted.as_str(),
-                rec.bounding_box().center() - txt_offset,
-                VerticalPosition::Bottom,
-                HorizontalAlignment::Center,
-                FontColor::Transparent(BinaryColor::On),
-                &mut self.display.color_converted(),
-            ).unwrap();
-
-            font_small.render_aligned(
-                format_args!("{}°|{}°", daily.temp.min.round(), daily.temp.max.round()),
-                rec.bounding_box().center() + txt_offset,
-                VerticalPosition::Top,
-                HorizontalAlignment::Center,
-                FontColor::Transparent(BinaryColor::On),
-                &mut self.display,
-            ).unwrap();
-
-
-        }
-        Ok(())
-    }
-
+    fn draw_weather_report(&mut self) -> Result<()> {
+        let Some(weather) = self.reports[self.current_location].as_ref() else {
+            return Ok(());
+        };
+        let locations: Vec<_> = self.locations.iter()
+            .zip(self.reports.iter())
+            .filter_map(|(location, report)| report.as_ref().map(|weather| LocationWeather {
+                name: &location.name,
+                weather,
+            }))
+            .collect();
+
+        let view = ViewModel {
+            weather,
+            location_name: &self.locations[self.current_location].name,
+            locations: &locations,
+            theme: &*self.icon_theme,
+        };
 
-    fn debug_draw_rect(&mut self) -> Result<()> {
-        let style = PrimitiveStyleBuilder::new()
-            .stroke_color(BinaryColor::On)
-            .stroke_width(1)
-            .build();
-        // self.rect.viewport.into_styled(style)
-        //     .draw(&mut self.display.color_converted())?;
-        // self.rect.current_weather.into_styled(style)
-        //     .draw(&mut self.display.color_converted())?;
-        // self.rect.weather_icon.into_styled(style)
-        //     .draw(&mut self.display.color_converted())?;
-        // self.rect.current_temp.into_styled(style)
-        //     .draw(&mut self.display.color_converted())?;
-        // self.rect.feels_like.into_styled(style)
-        //     .draw(&mut self.display.color_converted())?;
-        // self.rect.current_temp_unit.into_styled(style)
-        //     .draw(&mut self.display.color_converted())?;
-        // self.rect.date_location.into_styled(style)
-        //     .draw(&mut self.display.color_converted())?;
-        // self.rect.forecast.into_styled(style)
-        //     .draw(&mut self.display.color_converted())?;
-        //
-        // for rec in self.rect.forecasts.iter() {
-        //     rec.into_styled(style)
-        //         .draw(&mut self.display.color_converted())?;
-        // }
-
-        self.rect.metrics.into_styled(style)
-            .draw(&mut self.display.color_converted())?;
-        self.rect.chart.into_styled(style)
-            .draw(&mut self.display.color_converted())?;
+        let page = self.current
+            .and_then(|page| self.pages.iter().find(|screen| screen.page == page))
+            .unwrap_or(&self.pages[0]);
+        page.screen.draw(&view, &mut self.display)?;
 
         Ok(())
     }
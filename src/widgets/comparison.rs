@@ -0,0 +1,109 @@
+use std::fmt::Debug;
+use anyhow::Result;
+use embedded_graphics::{
+    geometry::*,
+    image::*,
+    pixelcolor::BinaryColor,
+    prelude::*,
+    primitives::Rectangle,
+};
+use u8g2_fonts::{
+    FontRenderer,
+    types::*,
+    fonts,
+};
+use crate::display::dither::{Dithered, DitherMode};
+use crate::icons::{fitting_sheet, glyphs::GlyphSheet, load};
+use crate::owm::icons::get_icon_for_current_weather;
+use crate::text::ellipsize;
+use crate::widgets::{draw_error, ViewModel, Widget, WidgetOptions};
+
+const ICON_DITHERING: DitherMode = DitherMode::Atkinson;
+const SPACING: u32 = 8;
+
+/// One row per location with its current conditions and today's range
+pub struct LocationComparison {
+    font: FontRenderer,
+    sheet: &'static GlyphSheet,
+}
+
+impl LocationComparison {
+    /// Box the icons are fitted in, unless the layout gives one
+    pub const DEFAULT_ICON_SIZE: u32 = 64;
+
+    pub fn new(options: &WidgetOptions<'_>) -> Result<Self> {
+        let size = options.u64_or("icon_size", Self::DEFAULT_ICON_SIZE as u64)? as u32;
+        Ok(LocationComparison {
+            font: options.font_or(FontRenderer::new::<fonts::u8g2_font_profont29_tf>())?,
+            sheet: fitting_sheet(Size::new(size, size))?,
+        })
+    }
+}
+
+impl<D> Widget<D> for LocationComparison
+    where
+        D: DrawTarget<Color = BinaryColor>,
+        D::Error: Debug,
+{
+    fn measure(&self, view: &ViewModel<'_>, available: Size) -> Size {
+        Size::new(available.width, view.locations.len() as u32 * (self.sheet.size + SPACING))
+    }
+
+    fn draw(&self, view: &ViewModel<'_>, region: Rectangle, target: &mut D) -> Result<()> {
+        let small = FontRenderer::new::<fonts::u8g2_font_profont22_tf>();
+        let row_height = self.sheet.size + SPACING;
+        let rows = (region.size.height / row_height) as usize;
+        // Name on the left half, temperatures on the right
+        let name_width = (region.size.width / 2).saturating_sub(self.sheet.size + SPACING);
+
+        for (index, location) in view.locations.iter().take(rows).enumerate() {
+            let top_left = region.top_left + Point::new(0, (row_height * index as u32) as i32);
+            let middle = top_left.y + (self.sheet.size / 2) as i32;
+            let Some(current) = location.weather.current.as_ref() else {
+                continue;
+            };
+
+            let icon = load(self.sheet, get_icon_for_current_weather(view.theme, current))?;
+            Image::new(&Dithered::new(&icon, ICON_DITHERING), top_left)
+                .draw(target)
+                .map_err(draw_error)?;
+
+            let name_left = top_left.x + (self.sheet.size + SPACING) as i32;
+            self.font.render(
+                ellipsize(&self.font, location.name, name_width)?.as_str(),
+                Point::new(name_left, middle),
+                VerticalPosition::Center,
+                FontColor::Transparent(BinaryColor::On),
+                target,
+            ).map_err(draw_error)?;
+
+            let right = region.center().x;
+            self.font.render(
+                format_args!("{}°", current.temp.round() as i32),
+                Point::new(right, middle),
+                VerticalPosition::Center,
+                FontColor::Transparent(BinaryColor::On),
+                target,
+            ).map_err(draw_error)?;
+
+            let range = location.weather.daily.as_deref()
+                .and_then(|daily| daily.first())
+                .map(|today| format!("{}°|{}°", today.temp.min.round() as i32, today.temp.max.round() as i32));
+            let description = current.weather.first().map(|condition| condition.description.as_str()).unwrap_or_default();
+            let details = match range {
+                Some(range) => format!("{}  {}", range, description),
+                None => description.to_string(),
+            };
+            let details_left = right + 96;
+            small.render(
+                ellipsize(&small, &details, (region.top_left.x + region.size.width as i32 - details_left).max(0) as u32)?.as_str(),
+                Point::new(details_left, middle),
+                VerticalPosition::Center,
+                FontColor::Transparent(BinaryColor::On),
+                target,
+            ).map_err(draw_error)?;
+        }
+
+        Ok(())
+    }
+}
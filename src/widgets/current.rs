@@ -0,0 +1,171 @@
+use std::fmt::Debug;
+use anyhow::Result;
+use embedded_graphics::{
+    geometry::*,
+    image::*,
+    pixelcolor::BinaryColor,
+    prelude::*,
+    primitives::*,
+};
+use u8g2_fonts::{
+    FontRenderer,
+    types::*,
+    fonts,
+};
+use crate::display::dither::{Dithered, DitherMode};
+use crate::icons::{fitting_sheet, glyphs::GlyphSheet, load};
+use crate::owm::icons::get_icon_for_current_weather;
+use crate::widgets::{draw_error, ViewModel, Widget, WidgetOptions};
+
+const ICON_DITHERING: DitherMode = DitherMode::FloydSteinberg;
+
+/// Large icon of the current conditions, centered in its region
+pub struct CurrentWeatherIcon {
+    sheet: &'static GlyphSheet,
+}
+
+impl CurrentWeatherIcon {
+    /// Box the icons are fitted in, unless the layout gives one
+    pub const DEFAULT_SIZE: u32 = 196;
+
+    pub fn new(options: &WidgetOptions<'_>) -> Result<Self> {
+        let size = options.u64_or("size", Self::DEFAULT_SIZE as u64)? as u32;
+        Ok(CurrentWeatherIcon {
+            sheet: fitting_sheet(Size::new(size, size))?,
+        })
+    }
+}
+
+impl<D> Widget<D> for CurrentWeatherIcon
+    where
+        D: DrawTarget<Color = BinaryColor>,
+        D::Error: Debug,
+{
+    fn measure(&self, _view: &ViewModel<'_>, _available: Size) -> Size {
+        Size::new(self.sheet.size, self.sheet.size)
+    }
+
+    fn draw(&self, view: &ViewModel<'_>, region: Rectangle, target: &mut D) -> Result<()> {
+        let current = view.current()?;
+        let icon = load(self.sheet, get_icon_for_current_weather(view.theme, current))?;
+        let dithered = Dithered::new(&icon, ICON_DITHERING);
+
+        // Icons are wider than their region, their horizontal padding is allowed to overflow
+        let top_left = Point::new(region.center().x - dithered.size().width as i32 / 2, region.top_left.y);
+        Image::new(&dithered, top_left)
+            .draw(target)
+            .map_err(draw_error)?;
+
+        Ok(())
+    }
+}
+
+/// Current temperature in large digits
+pub struct CurrentTemperature {
+    font: FontRenderer,
+}
+
+impl CurrentTemperature {
+    pub fn new(options: &WidgetOptions<'_>) -> Result<Self> {
+        Ok(CurrentTemperature {
+            font: options.font_or(FontRenderer::new::<fonts::u8g2_font_logisoso92_tn>())?,
+        })
+    }
+}
+
+impl<D> Widget<D> for CurrentTemperature
+    where
+        D: DrawTarget<Color = BinaryColor>,
+        D::Error: Debug,
+{
+    fn measure(&self, _view: &ViewModel<'_>, available: Size) -> Size {
+        available
+    }
+
+    fn draw(&self, view: &ViewModel<'_>, region: Rectangle, target: &mut D) -> Result<()> {
+        let current = view.current()?;
+
+        self.font.render_aligned(
+            format_args!("{}", current.temp.round() as i32),
+            region.center(),
+            VerticalPosition::Center,
+            HorizontalAlignment::Center,
+            FontColor::Transparent(BinaryColor::On),
+            target,
+        ).map_err(draw_error)?;
+
+        Ok(())
+    }
+}
+
+/// Degree sign next to the large temperature, the digits font doesn't have one
+pub struct TemperatureUnit;
+
+impl TemperatureUnit {
+    const DIAMETER: u32 = 12;
+}
+
+impl<D> Widget<D> for TemperatureUnit
+    where
+        D: DrawTarget<Color = BinaryColor>,
+        D::Error: Debug,
+{
+    fn measure(&self, _view: &ViewModel<'_>, _available: Size) -> Size {
+        Size::new(Self::DIAMETER, Self::DIAMETER)
+    }
+
+    fn draw(&self, _view: &ViewModel<'_>, region: Rectangle, target: &mut D) -> Result<()> {
+        let unit_style = PrimitiveStyleBuilder::new()
+            .stroke_width(4)
+            .stroke_color(BinaryColor::On)
+            .build();
+
+        let circle_center = Point::new(Self::DIAMETER as i32 / 2, Self::DIAMETER as i32 / 2);
+        let offset = Point::new(0, 30); // Puts the circle at the top of the 92px digits
+        Circle::new(
+            region.center() - offset - circle_center,
+            Self::DIAMETER
+        ).into_styled(unit_style)
+            .draw(target)
+            .map_err(draw_error)?;
+
+        Ok(())
+    }
+}
+
+pub struct FeelsLike {
+    font: FontRenderer,
+}
+
+impl FeelsLike {
+    pub fn new(options: &WidgetOptions<'_>) -> Result<Self> {
+        Ok(FeelsLike {
+            font: options.font_or(FontRenderer::new::<fonts::u8g2_font_profont22_tf>())?,
+        })
+    }
+}
+
+impl<D> Widget<D> for FeelsLike
+    where
+        D: DrawTarget<Color = BinaryColor>,
+        D::Error: Debug,
+{
+    fn measure(&self, _view: &ViewModel<'_>, available: Size) -> Size {
+        Size::new(available.width, 22)
+    }
+
+    fn draw(&self, view: &ViewModel<'_>, region: Rectangle, target: &mut D) -> Result<()> {
+        let current = view.current()?;
+
+        self.font.render_aligned(
+            format_args!("Feels Like {}°", current.feels_like.round() as i32),
+            region.center(),
+            VerticalPosition::Center,
+            HorizontalAlignment::Center,
+            FontColor::Transparent(BinaryColor::On),
+            target,
+        ).map_err(draw_error)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,68 @@
+use std::fmt::Debug;
+use anyhow::Result;
+use embedded_graphics::{
+    geometry::*,
+    pixelcolor::BinaryColor,
+    prelude::*,
+    primitives::Rectangle,
+};
+use u8g2_fonts::{
+    FontRenderer,
+    types::*,
+    fonts,
+};
+use crate::widgets::{draw_error, ViewModel, Widget, WidgetOptions};
+
+/// Location name with the date of the report below, right aligned
+pub struct DateLocation {
+    /// Font of the location name
+    font: FontRenderer,
+}
+
+impl DateLocation {
+    pub fn new(options: &WidgetOptions<'_>) -> Result<Self> {
+        Ok(DateLocation {
+            font: options.font_or(FontRenderer::new::<fonts::u8g2_font_profont29_tf>())?,
+        })
+    }
+}
+
+impl<D> Widget<D> for DateLocation
+    where
+        D: DrawTarget<Color = BinaryColor>,
+        D::Error: Debug,
+{
+    fn measure(&self, _view: &ViewModel<'_>, available: Size) -> Size {
+        Size::new(available.width, 29 + 22)
+    }
+
+    fn draw(&self, view: &ViewModel<'_>, region: Rectangle, target: &mut D) -> Result<()> {
+        let current = view.current()?;
+        let large = &self.font;
+        let font = FontRenderer::new::<fonts::u8g2_font_profont22_tf>();
+
+        let offset_dt = time::OffsetDateTime::from_unix_timestamp(current.dt as i64)?;
+        let format = time::format_description::parse("[weekday], [day] [month repr:long] [year]")?;
+        let formatted = offset_dt.format(&format)?;
+
+        large.render_aligned(
+            view.location_name,
+            region.anchor_point(AnchorPoint::TopRight),
+            VerticalPosition::Top,
+            HorizontalAlignment::Right,
+            FontColor::Transparent(BinaryColor::On),
+            target,
+        ).map_err(draw_error)?;
+
+        font.render_aligned(
+            formatted.as_str(),
+            region.anchor_point(AnchorPoint::TopRight) + Point::new(0, 29),
+            VerticalPosition::Top,
+            HorizontalAlignment::Right,
+            FontColor::Transparent(BinaryColor::On),
+            target,
+        ).map_err(draw_error)?;
+
+        Ok(())
+    }
+}
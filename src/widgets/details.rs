@@ -0,0 +1,220 @@
+use std::fmt::Debug;
+use anyhow::Result;
+use embedded_graphics::{
+    geometry::*,
+    pixelcolor::BinaryColor,
+    prelude::*,
+    primitives::Rectangle,
+};
+use u8g2_fonts::{
+    FontRenderer,
+    types::*,
+    fonts,
+};
+use crate::text::{ellipsize, wrap};
+use crate::widgets::{draw_error, ViewModel, Widget, WidgetOptions};
+
+const LINE_SPACING: u32 = 4;
+
+/// Hourly forecast as text, one line per hour, split in columns
+pub struct HourlyTable {
+    font: FontRenderer,
+    hours: usize,
+    columns: usize,
+}
+
+impl HourlyTable {
+    pub fn new(options: &WidgetOptions<'_>) -> Result<Self> {
+        Ok(HourlyTable {
+            font: options.font_or(FontRenderer::new::<fonts::u8g2_font_profont17_tf>())?,
+            hours: options.u64_or("hours", 48)? as usize,
+            columns: options.u64_or("columns", 3)?.max(1) as usize,
+        })
+    }
+}
+
+impl<D> Widget<D> for HourlyTable
+    where
+        D: DrawTarget<Color = BinaryColor>,
+        D::Error: Debug,
+{
+    fn measure(&self, _view: &ViewModel<'_>, available: Size) -> Size {
+        available
+    }
+
+    fn draw(&self, view: &ViewModel<'_>, region: Rectangle, target: &mut D) -> Result<()> {
+        let hourly = view.hourly()?;
+        let format = time::format_description::parse("[weekday repr:short] [hour]h")?;
+
+        let hours = self.hours.min(hourly.len());
+        let rows = (hours + self.columns - 1) / self.columns;
+        let column_width = region.size.width / self.columns as u32;
+        let line_height = line_height(&self.font);
+
+        for (index, hour) in hourly.iter().take(hours).enumerate() {
+            let column = (index / rows.max(1)) as u32;
+            let row = (index % rows.max(1)) as u32;
+            let position = region.top_left + Point::new((column * column_width) as i32, (row * line_height) as i32);
+
+            let condition = hour.weather.first().map(|condition| condition.main.as_str()).unwrap_or_default();
+            let line = format!(
+                "{} {:>3}° {:>3}% {}",
+                view.local_time(hour.dt)?.format(&format)?,
+                hour.temp.round() as i32,
+                (hour.pop * 100.0).round() as u32,
+                condition,
+            );
+
+            render_line(&self.font, &line, position, column_width, target)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Daily forecast as text, one line per day
+pub struct DailyTable {
+    font: FontRenderer,
+    days: usize,
+}
+
+impl DailyTable {
+    pub fn new(options: &WidgetOptions<'_>) -> Result<Self> {
+        Ok(DailyTable {
+            font: options.font_or(FontRenderer::new::<fonts::u8g2_font_profont22_tf>())?,
+            days: options.u64_or("days", 8)? as usize,
+        })
+    }
+}
+
+impl<D> Widget<D> for DailyTable
+    where
+        D: DrawTarget<Color = BinaryColor>,
+        D::Error: Debug,
+{
+    fn measure(&self, _view: &ViewModel<'_>, available: Size) -> Size {
+        available
+    }
+
+    fn draw(&self, view: &ViewModel<'_>, region: Rectangle, target: &mut D) -> Result<()> {
+        let daily = view.daily()?;
+        let format = time::format_description::parse("[weekday repr:short] [day]")?;
+
+        let days = self.days.min(daily.len());
+        if days == 0 {
+            return Ok(());
+        }
+        // Spread the lines over the whole region
+        let row_height = (region.size.height / days as u32).max(line_height(&self.font));
+
+        for (index, day) in daily.iter().take(days).enumerate() {
+            let position = region.top_left + Point::new(0, (row_height * index as u32) as i32);
+
+            let description = day.weather.first().map(|condition| condition.description.as_str()).unwrap_or_default();
+            let line = format!(
+                "{} {:>3}°|{:>3}° {:>3}% {:>3.0}m/s {}",
+                view.local_time(day.dt)?.format(&format)?,
+                day.temp.min.round() as i32,
+                day.temp.max.round() as i32,
+                (day.pop * 100.0).round() as u32,
+                day.wind_speed,
+                description,
+            );
+
+            render_line(&self.font, &line, position, region.size.width, target)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Active weather alerts with their validity and the beginning of their description
+pub struct AlertList {
+    font: FontRenderer,
+    /// Lines of description shown per alert
+    lines: usize,
+}
+
+impl AlertList {
+    pub fn new(options: &WidgetOptions<'_>) -> Result<Self> {
+        Ok(AlertList {
+            font: options.font_or(FontRenderer::new::<fonts::u8g2_font_profont22_tf>())?,
+            lines: options.u64_or("lines", 4)? as usize,
+        })
+    }
+}
+
+impl<D> Widget<D> for AlertList
+    where
+        D: DrawTarget<Color = BinaryColor>,
+        D::Error: Debug,
+{
+    fn measure(&self, _view: &ViewModel<'_>, available: Size) -> Size {
+        available
+    }
+
+    fn draw(&self, view: &ViewModel<'_>, region: Rectangle, target: &mut D) -> Result<()> {
+        let title = FontRenderer::new::<fonts::u8g2_font_profont29_tf>();
+        let small = FontRenderer::new::<fonts::u8g2_font_profont17_tf>();
+        let format = time::format_description::parse("[weekday repr:short] [hour]:[minute]")?;
+        let bottom = region.top_left.y + region.size.height as i32;
+        let mut y = region.top_left.y;
+
+        let alerts = view.alerts();
+        if alerts.is_empty() {
+            render_line(&self.font, "No active alerts", region.top_left, region.size.width, target)?;
+            return Ok(());
+        }
+
+        for alert in alerts {
+            let lines = [
+                (&title, alert.event.clone()),
+                (&small, format!(
+                    "{} - {}, {}",
+                    view.local_time(alert.start)?.format(&format)?,
+                    view.local_time(alert.end)?.format(&format)?,
+                    alert.sender_name,
+                )),
+            ].into_iter()
+                .chain(wrap(&self.font, &alert.description, region.size.width)?
+                    .into_iter()
+                    .take(self.lines)
+                    .map(|line| (&self.font, line)));
+
+            for (font, line) in lines {
+                let height = line_height(font);
+                if y + height as i32 > bottom {
+                    return Ok(());
+                }
+                render_line(font, &line, Point::new(region.top_left.x, y), region.size.width, target)?;
+                y += height as i32;
+            }
+            y += line_height(&self.font) as i32;
+        }
+
+        Ok(())
+    }
+}
+
+fn line_height(font: &FontRenderer) -> u32 {
+    font.get_default_line_height() + LINE_SPACING
+}
+
+/// Draws `text` from `position`, cut with an ellipsis to fit in `width`
+fn render_line<D>(font: &FontRenderer, text: &str, position: Point, width: u32, target: &mut D) -> Result<()>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+        D::Error: Debug,
+{
+    let text = ellipsize(font, text, width)?;
+
+    font.render(
+        text.as_str(),
+        position,
+        VerticalPosition::Top,
+        FontColor::Transparent(BinaryColor::On),
+        target,
+    ).map_err(draw_error)?;
+
+    Ok(())
+}
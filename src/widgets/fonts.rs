@@ -0,0 +1,15 @@
+use anyhow::{bail, Result};
+use u8g2_fonts::{FontRenderer, fonts};
+
+/// Fonts layouts can refer to by name
+pub fn font_by_name(name: &str) -> Result<FontRenderer> {
+    Ok(match name {
+        "profont17" => FontRenderer::new::<fonts::u8g2_font_profont17_tf>(),
+        "profont22" => FontRenderer::new::<fonts::u8g2_font_profont22_tf>(),
+        "profont29" => FontRenderer::new::<fonts::u8g2_font_profont29_tf>(),
+        "logisoso46" => FontRenderer::new::<fonts::u8g2_font_logisoso46_tn>(),
+        "logisoso62" => FontRenderer::new::<fonts::u8g2_font_logisoso62_tn>(),
+        "logisoso92" => FontRenderer::new::<fonts::u8g2_font_logisoso92_tn>(),
+        _ => bail!("Unknown font '{}'", name),
+    })
+}
@@ -0,0 +1,103 @@
+use std::fmt::Debug;
+use anyhow::Result;
+use embedded_graphics::{
+    geometry::*,
+    image::*,
+    pixelcolor::BinaryColor,
+    prelude::*,
+    primitives::Rectangle,
+};
+use u8g2_fonts::{
+    FontRenderer,
+    types::*,
+    fonts,
+};
+use crate::display::dither::{Dithered, DitherMode};
+use crate::icons::{fitting_sheet, glyphs::GlyphSheet, load};
+use crate::owm::icons::get_icon_for_daily_forecast;
+use crate::widgets::{draw_error, ViewModel, Widget, WidgetOptions};
+
+const ICON_DITHERING: DitherMode = DitherMode::Atkinson;
+const TEXT_SPACING: u32 = 8;
+
+/// One column per day with the day of week, icon and min/max temperatures
+pub struct DailyForecastStrip {
+    days: usize,
+    sheet: &'static GlyphSheet,
+}
+
+impl DailyForecastStrip {
+    /// Box the icons are fitted in, unless the layout gives one
+    pub const DEFAULT_ICON_SIZE: u32 = 64;
+
+    pub fn new(options: &WidgetOptions<'_>) -> Result<Self> {
+        let size = options.u64_or("icon_size", Self::DEFAULT_ICON_SIZE as u64)? as u32;
+        Ok(DailyForecastStrip {
+            days: options.u64_or("days", 5)? as usize,
+            sheet: fitting_sheet(Size::new(size, size))?,
+        })
+    }
+}
+
+impl<D> Widget<D> for DailyForecastStrip
+    where
+        D: DrawTarget<Color = BinaryColor>,
+        D::Error: Debug,
+{
+    fn measure(&self, _view: &ViewModel<'_>, available: Size) -> Size {
+        Size::new(available.width, self.sheet.size + 2 * (TEXT_SPACING + 22))
+    }
+
+    fn draw(&self, view: &ViewModel<'_>, region: Rectangle, target: &mut D) -> Result<()> {
+        let forecast = view.daily()?;
+        let font = FontRenderer::new::<fonts::u8g2_font_profont22_tf>();
+        let font_small = FontRenderer::new::<fonts::u8g2_font_profont17_tf>();
+        let format = time::format_description::parse("[weekday repr:short]")?;
+
+        let days = self.days.min(forecast.len());
+        if days == 0 {
+            return Ok(());
+        }
+        let column_size = Size::new(region.size.width / days as u32, region.size.height);
+
+        for (index, daily) in forecast.iter().take(days).enumerate() {
+            let column = Rectangle::new(
+                region.top_left + Point::new((column_size.width * index as u32) as i32, 0),
+                column_size,
+            );
+
+            let icon = load(self.sheet, get_icon_for_daily_forecast(view.theme, daily))?;
+            let img_center_offset = Point::new((self.sheet.size / 2) as i32, (self.sheet.size / 2) as i32);
+
+            Image::new(&Dithered::new(&icon, ICON_DITHERING), column.center() - img_center_offset)
+                .draw(target)
+                .map_err(draw_error)?;
+
+            let txt_offset = Point::new(0, (self.sheet.size / 2 + TEXT_SPACING) as i32);
+
+            // Draw day of week
+            let offset_dt = time::OffsetDateTime::from_unix_timestamp(daily.dt as i64)?;
+            let day_formatted = offset_dt.format(&format)?;
+
+            font.render_aligned(
+                day_formatted.as_str(),
+                column.center() - txt_offset,
+                VerticalPosition::Bottom,
+                HorizontalAlignment::Center,
+                FontColor::Transparent(BinaryColor::On),
+                target,
+            ).map_err(draw_error)?;
+
+            font_small.render_aligned(
+                format_args!("{}°|{}°", daily.temp.min.round(), daily.temp.max.round()),
+                column.center() + txt_offset,
+                VerticalPosition::Top,
+                HorizontalAlignment::Center,
+                FontColor::Transparent(BinaryColor::On),
+                target,
+            ).map_err(draw_error)?;
+        }
+
+        Ok(())
+    }
+}
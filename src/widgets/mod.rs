@@ -0,0 +1,216 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use anyhow::{anyhow, bail, Context, Result};
+use embedded_graphics::{
+    geometry::*,
+    pixelcolor::BinaryColor,
+    prelude::*,
+    primitives::Rectangle,
+};
+use serde_json::{Map, Value};
+use u8g2_fonts::FontRenderer;
+use crate::icons::theme::IconTheme;
+use crate::layout::Layout;
+use time::{OffsetDateTime, UtcOffset};
+use crate::owm::model::{Alert, CurrentWeather, DailyForecast, HourlyForecast, MinuteForecast, WeatherData};
+
+pub mod comparison;
+pub mod current;
+pub mod date_location;
+pub mod details;
+pub mod forecast;
+pub mod moon;
+pub mod nowcast;
+pub mod summary;
+pub mod sun;
+pub mod wind;
+pub mod fonts;
+
+use fonts::font_by_name;
+
+/// Latest report of a location
+pub struct LocationWeather<'a> {
+    pub name: &'a str,
+    pub weather: &'a WeatherData,
+}
+
+/// Data available to widgets while drawing a screen
+pub struct ViewModel<'a> {
+    pub weather: &'a WeatherData,
+    pub location_name: &'a str,
+    /// Every location with a report, including the one on screen
+    pub locations: &'a [LocationWeather<'a>],
+    pub theme: &'a dyn IconTheme,
+}
+
+#[allow(dead_code)]
+impl<'a> ViewModel<'a> {
+    pub fn current(&self) -> Result<&'a CurrentWeather> {
+        self.weather.current.as_ref().ok_or_else(|| anyhow!("Weather report has no current conditions"))
+    }
+
+    pub fn daily(&self) -> Result<&'a [DailyForecast]> {
+        self.weather.daily.as_deref().ok_or_else(|| anyhow!("Weather report has no daily forecast"))
+    }
+
+    pub fn hourly(&self) -> Result<&'a [HourlyForecast]> {
+        self.weather.hourly.as_deref().ok_or_else(|| anyhow!("Weather report has no hourly forecast"))
+    }
+
+    /// Minute by minute precipitation for the next hour, empty where
+    /// OpenWeather doesn't provide it
+    pub fn minutely(&self) -> &'a [MinuteForecast] {
+        self.weather.minutely.as_deref().unwrap_or_default()
+    }
+
+    /// Active alerts, empty when there are none
+    pub fn alerts(&self) -> &'a [Alert] {
+        self.weather.alerts.as_deref().unwrap_or_default()
+    }
+
+    /// Timestamp of the report converted to the time zone of the location
+    pub fn local_time(&self, timestamp: u64) -> Result<OffsetDateTime> {
+        let offset = UtcOffset::from_whole_seconds(self.weather.timezone_offset as i32)?;
+        Ok(OffsetDateTime::from_unix_timestamp(timestamp as i64)?.to_offset(offset))
+    }
+}
+
+/// A section of the screen.
+///
+/// Widgets draw themselves inside the region the layout gives them, on any
+/// binary draw target.
+pub trait Widget<D>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+        D::Error: Debug,
+{
+    /// Size the widget would like to use out of `available`
+    fn measure(&self, view: &ViewModel<'_>, available: Size) -> Size;
+
+    fn draw(&self, view: &ViewModel<'_>, region: Rectangle, target: &mut D) -> Result<()>;
+}
+
+/// Settings a widget was placed with in the layout
+pub struct WidgetOptions<'a> {
+    pub font: Option<&'a str>,
+    pub values: &'a Map<String, Value>,
+}
+
+impl WidgetOptions<'_> {
+    pub fn font_or(&self, default: FontRenderer) -> Result<FontRenderer> {
+        match self.font {
+            Some(name) => font_by_name(name),
+            None => Ok(default),
+        }
+    }
+
+    pub fn u64_or(&self, key: &str, default: u64) -> Result<u64> {
+        match self.values.get(key) {
+            Some(value) => value.as_u64().ok_or_else(|| anyhow!("Option '{}' must be a positive integer", key)),
+            None => Ok(default),
+        }
+    }
+
+    pub fn str_or<'b>(&'b self, key: &str, default: &'b str) -> Result<&'b str> {
+        match self.values.get(key) {
+            Some(value) => value.as_str().ok_or_else(|| anyhow!("Option '{}' must be a string", key)),
+            None => Ok(default),
+        }
+    }
+}
+
+type WidgetFactory<D> = Box<dyn Fn(&WidgetOptions<'_>) -> Result<Box<dyn Widget<D>>>>;
+
+/// Widget constructors by name, so layouts can reference them
+pub struct WidgetRegistry<D> {
+    factories: HashMap<String, WidgetFactory<D>>,
+}
+
+impl<D> WidgetRegistry<D>
+    where
+        D: DrawTarget<Color = BinaryColor> + 'static,
+        D::Error: Debug,
+{
+    pub fn new() -> Self {
+        WidgetRegistry {
+            factories: HashMap::new(),
+        }
+    }
+
+    /// Registry with every built-in widget under its default name
+    pub fn with_defaults() -> Self {
+        let mut registry = Self::new();
+        registry.register("current_weather_icon", current::CurrentWeatherIcon::new);
+        registry.register("current_temperature", current::CurrentTemperature::new);
+        registry.register("temperature_unit", |_| Ok(current::TemperatureUnit));
+        registry.register("feels_like", current::FeelsLike::new);
+        registry.register("date_location", date_location::DateLocation::new);
+        registry.register("daily_forecast", forecast::DailyForecastStrip::new);
+        registry.register("hourly_table", details::HourlyTable::new);
+        registry.register("daily_table", details::DailyTable::new);
+        registry.register("alerts", details::AlertList::new);
+        registry.register("moon", moon::MoonPhaseWidget::new);
+        registry.register("sun_path", sun::SunPath::new);
+        registry.register("wind", wind::WindCompass::new);
+        registry.register("nowcast", nowcast::NowcastStrip::new);
+        registry.register("summary", summary::DailySummary::new);
+        registry.register("location_comparison", comparison::LocationComparison::new);
+        registry
+    }
+
+    pub fn register<W, F>(&mut self, name: impl Into<String>, factory: F)
+        where
+            W: Widget<D> + 'static,
+            F: Fn(&WidgetOptions<'_>) -> Result<W> + 'static,
+    {
+        self.factories.insert(name.into(), Box::new(move |options| {
+            let widget: Box<dyn Widget<D>> = Box::new(factory(options)?);
+            Ok(widget)
+        }));
+    }
+
+    pub fn create(&self, name: &str, options: &WidgetOptions<'_>) -> Result<Box<dyn Widget<D>>> {
+        match self.factories.get(name) {
+            Some(factory) => factory(options).with_context(|| format!("Invalid options for widget '{}'", name)),
+            None => bail!("No widget registered as '{}'", name),
+        }
+    }
+
+    /// Creates every widget placed in `layout`
+    pub fn build(&self, layout: &Layout) -> Result<Screen<D>> {
+        let widgets = layout.widgets()
+            .map(|placement| {
+                let options = WidgetOptions {
+                    font: placement.font,
+                    values: placement.options,
+                };
+                Ok((self.create(placement.widget, &options)?, placement.rect))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Screen { widgets })
+    }
+}
+
+/// Widgets of a layout, ready to draw
+pub struct Screen<D> {
+    widgets: Vec<(Box<dyn Widget<D>>, Rectangle)>,
+}
+
+impl<D> Screen<D>
+    where
+        D: DrawTarget<Color = BinaryColor>,
+        D::Error: Debug,
+{
+    pub fn draw(&self, view: &ViewModel<'_>, target: &mut D) -> Result<()> {
+        for (widget, region) in self.widgets.iter() {
+            widget.draw(view, *region, target)?;
+        }
+        Ok(())
+    }
+}
+
+/// Errors of generic draw targets only implement `Debug`
+pub fn draw_error<E: Debug>(err: E) -> anyhow::Error {
+    anyhow!("Drawing failed: {:?}", err)
+}
@@ -0,0 +1,97 @@
+use std::fmt::Debug;
+use anyhow::{bail, Result};
+use embedded_graphics::{
+    geometry::*,
+    image::*,
+    pixelcolor::BinaryColor,
+    prelude::*,
+    primitives::Rectangle,
+};
+use u8g2_fonts::{
+    FontRenderer,
+    types::*,
+    fonts,
+};
+use crate::astronomy::moon::MoonPhase;
+use crate::display::dither::{Dithered, DitherMode};
+use crate::icons::{moon_phase_icon, MOON_ICON_SIZE};
+use crate::widgets::{draw_error, ViewModel, Widget, WidgetOptions};
+
+const ICON_DITHERING: DitherMode = DitherMode::FloydSteinberg;
+const TEXT_SPACING: u32 = 16;
+
+/// Today's moon phase icon with its name, illumination, rise and set times
+/// and the days left to the next full and new moon
+pub struct MoonPhaseWidget {
+    font: FontRenderer,
+    /// Use the outlined icon set
+    alternate: bool,
+}
+
+impl MoonPhaseWidget {
+    pub fn new(options: &WidgetOptions<'_>) -> Result<Self> {
+        let alternate = match options.str_or("icons", "filled")? {
+            "filled" => false,
+            "outline" => true,
+            other => bail!("Unknown moon icon set '{}', expected filled or outline", other),
+        };
+
+        Ok(MoonPhaseWidget {
+            font: options.font_or(FontRenderer::new::<fonts::u8g2_font_profont22_tf>())?,
+            alternate,
+        })
+    }
+}
+
+impl<D> Widget<D> for MoonPhaseWidget
+    where
+        D: DrawTarget<Color = BinaryColor>,
+        D::Error: Debug,
+{
+    fn measure(&self, _view: &ViewModel<'_>, available: Size) -> Size {
+        Size::new(available.width, MOON_ICON_SIZE.max(6 * 26))
+    }
+
+    fn draw(&self, view: &ViewModel<'_>, region: Rectangle, target: &mut D) -> Result<()> {
+        let Some(today) = view.daily()?.first() else {
+            return Ok(());
+        };
+        let phase = MoonPhase::new(today.moon_phase);
+        let format = time::format_description::parse("[hour]:[minute]")?;
+        // OpenWeather reports 0 when the moon doesn't rise or set that day
+        let time_of = |timestamp: u64| -> Result<String> {
+            match timestamp {
+                0 => Ok(String::from("--:--")),
+                timestamp => Ok(view.local_time(timestamp)?.format(&format)?),
+            }
+        };
+
+        let icon = moon_phase_icon(phase.step(), self.alternate);
+        Image::new(&Dithered::new(&icon, ICON_DITHERING), region.top_left)
+            .draw(target)
+            .map_err(draw_error)?;
+
+        let lines = [
+            phase.name().label().to_string(),
+            format!("Illumination {}%", (phase.illumination() * 100.0).round() as u32),
+            format!("Moonrise     {}", time_of(today.moonrise)?),
+            format!("Moonset      {}", time_of(today.moonset)?),
+            format!("Full moon in {} days", phase.days_to_full().round() as u32),
+            format!("New moon in  {} days", phase.days_to_new().round() as u32),
+        ];
+
+        let text_origin = region.top_left + Point::new((MOON_ICON_SIZE + TEXT_SPACING) as i32, 0);
+        let line_height = self.font.get_default_line_height() + 4;
+        for (index, line) in lines.iter().enumerate() {
+            self.font.render(
+                line.as_str(),
+                text_origin + Point::new(0, (line_height * index as u32) as i32),
+                VerticalPosition::Top,
+                FontColor::Transparent(BinaryColor::On),
+                target,
+            ).map_err(draw_error)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,122 @@
+use std::fmt::Debug;
+use anyhow::Result;
+use embedded_graphics::{
+    geometry::*,
+    pixelcolor::BinaryColor,
+    prelude::*,
+    primitives::*,
+};
+use u8g2_fonts::{
+    FontRenderer,
+    types::*,
+    fonts,
+};
+use crate::owm::nowcast::{Nowcast, WET_THRESHOLD};
+use crate::widgets::{draw_error, ViewModel, Widget, WidgetOptions};
+
+const MINUTES: usize = 60;
+/// Precipitation drawn as a full height bar, in mm/h
+const HEAVY_PRECIPITATION: f32 = 4.0;
+const SPACING: u32 = 8;
+
+/// Nowcast sentence above one bar per minute of precipitation for the next hour
+pub struct NowcastStrip {
+    font: FontRenderer,
+}
+
+impl NowcastStrip {
+    pub fn new(options: &WidgetOptions<'_>) -> Result<Self> {
+        Ok(NowcastStrip {
+            font: options.font_or(FontRenderer::new::<fonts::u8g2_font_profont22_tf>())?,
+        })
+    }
+}
+
+impl<D> Widget<D> for NowcastStrip
+    where
+        D: DrawTarget<Color = BinaryColor>,
+        D::Error: Debug,
+{
+    fn measure(&self, _view: &ViewModel<'_>, available: Size) -> Size {
+        available
+    }
+
+    fn draw(&self, view: &ViewModel<'_>, region: Rectangle, target: &mut D) -> Result<()> {
+        let small = FontRenderer::new::<fonts::u8g2_font_profont17_tf>();
+        let now = view.current()?.dt;
+        let minutes: Vec<_> = view.minutely().iter()
+            .filter(|minute| minute.dt + 60 > now)
+            .take(MINUTES)
+            .collect();
+
+        let text = match minutes.is_empty() {
+            true => String::from("No minutely forecast"),
+            false => Nowcast::from_minutes(view.minutely(), now).to_string(),
+        };
+        self.font.render(
+            text.as_str(),
+            region.top_left,
+            VerticalPosition::Top,
+            FontColor::Transparent(BinaryColor::On),
+            target,
+        ).map_err(draw_error)?;
+        if minutes.is_empty() {
+            return Ok(());
+        }
+
+        let label_height = small.get_default_line_height();
+        let text_height = self.font.get_default_line_height() + SPACING;
+        let chart_height = region.size.height.saturating_sub(text_height + label_height + SPACING);
+        let bar_width = (region.size.width / MINUTES as u32).max(1);
+        let baseline = region.top_left.y + (text_height + chart_height) as i32;
+        let fill = PrimitiveStyle::with_fill(BinaryColor::On);
+
+        for (index, minute) in minutes.iter().enumerate() {
+            if minute.precipitation < WET_THRESHOLD {
+                continue;
+            }
+            let intensity = (minute.precipitation / HEAVY_PRECIPITATION).min(1.0);
+            // Light precipitation still gets a visible bar
+            let height = ((chart_height as f32 * intensity).round() as u32).max(2);
+            Rectangle::new(
+                Point::new(region.top_left.x + (bar_width * index as u32) as i32, baseline - height as i32),
+                // Leave a gap between bars when they are wide enough
+                Size::new(if bar_width > 3 { bar_width - 1 } else { bar_width }, height),
+            )
+                .into_styled(fill)
+                .draw(target)
+                .map_err(draw_error)?;
+        }
+
+        let axis_width = bar_width * MINUTES as u32;
+        Line::new(
+            Point::new(region.top_left.x, baseline),
+            Point::new(region.top_left.x + axis_width as i32, baseline),
+        )
+            .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+            .draw(target)
+            .map_err(draw_error)?;
+
+        for minute in (0..=MINUTES as u32).step_by(15) {
+            let label = match minute {
+                0 => String::from("now"),
+                minute => format!("{}'", minute),
+            };
+            let x = region.top_left.x + (bar_width * minute) as i32;
+            small.render_aligned(
+                label.as_str(),
+                Point::new(x, baseline + SPACING as i32 / 2),
+                VerticalPosition::Top,
+                match minute {
+                    0 => HorizontalAlignment::Left,
+                    60 => HorizontalAlignment::Right,
+                    _ => HorizontalAlignment::Center,
+                },
+                FontColor::Transparent(BinaryColor::On),
+                target,
+            ).map_err(draw_error)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,53 @@
+use std::fmt::Debug;
+use anyhow::Result;
+use embedded_graphics::{
+    geometry::*,
+    pixelcolor::BinaryColor,
+    prelude::*,
+    primitives::Rectangle,
+};
+use u8g2_fonts::{
+    FontRenderer,
+    fonts,
+};
+use crate::text::fit;
+use crate::widgets::{fonts::font_by_name, ViewModel, Widget, WidgetOptions};
+
+const LINE_SPACING: u32 = 2;
+
+/// OpenWeather's summary of a day, word wrapped in its region.
+/// Switches to the smaller font when the text doesn't fit.
+pub struct DailySummary {
+    font: FontRenderer,
+    small_font: FontRenderer,
+    day: usize,
+}
+
+impl DailySummary {
+    pub fn new(options: &WidgetOptions<'_>) -> Result<Self> {
+        Ok(DailySummary {
+            font: options.font_or(FontRenderer::new::<fonts::u8g2_font_profont22_tf>())?,
+            small_font: font_by_name(options.str_or("small_font", "profont17")?)?,
+            day: options.u64_or("day", 0)? as usize,
+        })
+    }
+}
+
+impl<D> Widget<D> for DailySummary
+    where
+        D: DrawTarget<Color = BinaryColor>,
+        D::Error: Debug,
+{
+    fn measure(&self, _view: &ViewModel<'_>, available: Size) -> Size {
+        available
+    }
+
+    fn draw(&self, view: &ViewModel<'_>, region: Rectangle, target: &mut D) -> Result<()> {
+        let Some(day) = view.daily()?.get(self.day) else {
+            return Ok(());
+        };
+
+        let paragraph = fit(&day.summary, &[&self.font, &self.small_font], region.size, LINE_SPACING)?;
+        paragraph.draw(region.top_left, target)
+    }
+}
@@ -0,0 +1,144 @@
+use std::fmt::Debug;
+use anyhow::Result;
+use embedded_graphics::{
+    geometry::*,
+    image::*,
+    pixelcolor::BinaryColor,
+    prelude::*,
+    primitives::*,
+};
+use u8g2_fonts::{
+    FontRenderer,
+    types::*,
+    fonts,
+};
+use crate::astronomy::sun::{civil_twilight, day_progress};
+use crate::display::dither::{Dithered, DitherMode};
+use crate::icons::{sunrise_icon, sunset_icon, SUN_ICON_SIZE};
+use crate::widgets::{draw_error, ViewModel, Widget, WidgetOptions};
+
+const ICON_DITHERING: DitherMode = DitherMode::Atkinson;
+const SUN_DIAMETER: u32 = 20;
+const MAX_RADIUS: u32 = 160;
+const SPACING: u32 = 8;
+
+/// Arc of the sun between sunrise and sunset with its current position,
+/// the day length, its change until tomorrow and civil twilight times
+pub struct SunPath {
+    font: FontRenderer,
+}
+
+impl SunPath {
+    pub fn new(options: &WidgetOptions<'_>) -> Result<Self> {
+        Ok(SunPath {
+            font: options.font_or(FontRenderer::new::<fonts::u8g2_font_profont22_tf>())?,
+        })
+    }
+}
+
+impl<D> Widget<D> for SunPath
+    where
+        D: DrawTarget<Color = BinaryColor>,
+        D::Error: Debug,
+{
+    fn measure(&self, _view: &ViewModel<'_>, available: Size) -> Size {
+        available
+    }
+
+    fn draw(&self, view: &ViewModel<'_>, region: Rectangle, target: &mut D) -> Result<()> {
+        let current = view.current()?;
+        let daily = view.daily()?;
+        let format = time::format_description::parse("[hour]:[minute]")?;
+        let time_of = |timestamp: u64| -> Result<String> {
+            Ok(view.local_time(timestamp)?.format(&format)?)
+        };
+        let line_style = PrimitiveStyle::with_stroke(BinaryColor::On, 1);
+
+        // Path of the sun above the horizon
+        let radius = (region.size.width.saturating_sub(2 * SUN_DIAMETER) / 2).min(MAX_RADIUS);
+        let center = Point::new(region.center().x, region.top_left.y + (SUN_DIAMETER + radius) as i32);
+        Arc::with_center(center, 2 * radius, 180.0.deg(), 180.0.deg())
+            .into_styled(line_style)
+            .draw(target)
+            .map_err(draw_error)?;
+        let horizon = (radius + SUN_DIAMETER) as i32;
+        Line::new(center - Point::new(horizon, 0), center + Point::new(horizon, 0))
+            .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 3))
+            .draw(target)
+            .map_err(draw_error)?;
+
+        if let Some(progress) = day_progress(current.dt, current.sunrise, current.sunset) {
+            let angle = std::f32::consts::PI * (1.0 + progress);
+            let position = center + Point::new(
+                (radius as f32 * angle.cos()).round() as i32,
+                (radius as f32 * angle.sin()).round() as i32,
+            );
+            Circle::with_center(position, SUN_DIAMETER)
+                .into_styled(PrimitiveStyle::with_fill(BinaryColor::On))
+                .draw(target)
+                .map_err(draw_error)?;
+        }
+
+        // Sunrise on the left, sunset on the right, under the horizon
+        let top = center.y + SPACING as i32;
+        let left = region.top_left.x;
+        let right = region.top_left.x + region.size.width as i32;
+        let icon_middle = top + (SUN_ICON_SIZE / 2) as i32;
+
+        Image::new(&Dithered::new(&sunrise_icon(), ICON_DITHERING), Point::new(left, top))
+            .draw(target)
+            .map_err(draw_error)?;
+        self.font.render_aligned(
+            time_of(current.sunrise)?.as_str(),
+            Point::new(left + SUN_ICON_SIZE as i32, icon_middle),
+            VerticalPosition::Center,
+            HorizontalAlignment::Left,
+            FontColor::Transparent(BinaryColor::On),
+            target,
+        ).map_err(draw_error)?;
+
+        Image::new(&Dithered::new(&sunset_icon(), ICON_DITHERING), Point::new(right - SUN_ICON_SIZE as i32, top))
+            .draw(target)
+            .map_err(draw_error)?;
+        self.font.render_aligned(
+            time_of(current.sunset)?.as_str(),
+            Point::new(right - SUN_ICON_SIZE as i32, icon_middle),
+            VerticalPosition::Center,
+            HorizontalAlignment::Right,
+            FontColor::Transparent(BinaryColor::On),
+            target,
+        ).map_err(draw_error)?;
+
+        let day_length = current.sunset.saturating_sub(current.sunrise);
+        let mut lines = vec![
+            format!("Day length {}h{:02}", day_length / 3600, day_length % 3600 / 60),
+        ];
+        if let [today, tomorrow, ..] = daily {
+            let change = (tomorrow.sunset as i64 - tomorrow.sunrise as i64) - (today.sunset as i64 - today.sunrise as i64);
+            lines.push(format!("Tomorrow   {:+} min", change / 60));
+        }
+        let date = view.local_time(current.dt)?.date();
+        match civil_twilight(date, view.weather.lat, view.weather.lon) {
+            Some((dawn, dusk)) => {
+                lines.push(format!("Civil dawn {}", time_of(dawn.max(0) as u64)?));
+                lines.push(format!("Civil dusk {}", time_of(dusk.max(0) as u64)?));
+            }
+            // Polar day or night
+            None => lines.push(String::from("No civil twilight")),
+        }
+
+        let line_height = self.font.get_default_line_height() + 4;
+        let text_top = top + (SUN_ICON_SIZE + SPACING) as i32;
+        for (index, line) in lines.iter().enumerate() {
+            self.font.render(
+                line.as_str(),
+                Point::new(left, text_top + (line_height * index as u32) as i32),
+                VerticalPosition::Top,
+                FontColor::Transparent(BinaryColor::On),
+                target,
+            ).map_err(draw_error)?;
+        }
+
+        Ok(())
+    }
+}
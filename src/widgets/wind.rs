@@ -0,0 +1,98 @@
+use std::fmt::Debug;
+use anyhow::Result;
+use embedded_graphics::{
+    geometry::*,
+    image::*,
+    pixelcolor::BinaryColor,
+    prelude::*,
+    primitives::Rectangle,
+};
+use u8g2_fonts::{
+    FontRenderer,
+    types::*,
+    fonts,
+};
+use crate::display::dither::{Dithered, DitherMode};
+use crate::icons::{beaufort_icon, wind_direction_icon, WIND_ICON_SIZE};
+use crate::wind::{cardinal, Beaufort};
+use crate::widgets::{draw_error, ViewModel, Widget, WidgetOptions};
+
+const ICON_DITHERING: DitherMode = DitherMode::Atkinson;
+const SPACING: u32 = 8;
+
+/// Current wind direction arrow with its compass point, speed and gusts,
+/// above the Beaufort force icon and description
+pub struct WindCompass {
+    font: FontRenderer,
+}
+
+impl WindCompass {
+    pub fn new(options: &WidgetOptions<'_>) -> Result<Self> {
+        Ok(WindCompass {
+            font: options.font_or(FontRenderer::new::<fonts::u8g2_font_profont22_tf>())?,
+        })
+    }
+
+    fn render_lines<D>(&self, lines: &[String], icon_top_left: Point, target: &mut D) -> Result<()>
+        where
+            D: DrawTarget<Color = BinaryColor>,
+            D::Error: Debug,
+    {
+        let line_height = self.font.get_default_line_height() + 4;
+        let text_height = line_height * lines.len() as u32;
+        // Vertically centered next to the icon
+        let top = icon_top_left.y + (WIND_ICON_SIZE.saturating_sub(text_height) / 2) as i32;
+        let left = icon_top_left.x + (WIND_ICON_SIZE + SPACING) as i32;
+
+        for (index, line) in lines.iter().enumerate() {
+            self.font.render(
+                line.as_str(),
+                Point::new(left, top + (line_height * index as u32) as i32),
+                VerticalPosition::Top,
+                FontColor::Transparent(BinaryColor::On),
+                target,
+            ).map_err(draw_error)?;
+        }
+        Ok(())
+    }
+}
+
+impl<D> Widget<D> for WindCompass
+    where
+        D: DrawTarget<Color = BinaryColor>,
+        D::Error: Debug,
+{
+    fn measure(&self, _view: &ViewModel<'_>, available: Size) -> Size {
+        Size::new(available.width, 2 * WIND_ICON_SIZE + SPACING)
+    }
+
+    fn draw(&self, view: &ViewModel<'_>, region: Rectangle, target: &mut D) -> Result<()> {
+        let current = view.current()?;
+        let beaufort = Beaufort::from_speed(current.wind_speed);
+
+        let direction_origin = region.top_left;
+        Image::new(&Dithered::new(&wind_direction_icon(current.wind_deg), ICON_DITHERING), direction_origin)
+            .draw(target)
+            .map_err(draw_error)?;
+        let mut lines = vec![
+            format!("{} {}°", cardinal(current.wind_deg), current.wind_deg),
+            format!("{:.0} m/s", current.wind_speed),
+        ];
+        if let Some(gust) = current.wind_gust {
+            lines.push(format!("Gusts {:.0} m/s", gust));
+        }
+        self.render_lines(&lines, direction_origin, target)?;
+
+        let beaufort_origin = region.top_left + Point::new(0, (WIND_ICON_SIZE + SPACING) as i32);
+        Image::new(&Dithered::new(&beaufort_icon(beaufort.force()), ICON_DITHERING), beaufort_origin)
+            .draw(target)
+            .map_err(draw_error)?;
+        let lines = [
+            format!("Force {}", beaufort.force()),
+            beaufort.description().to_string(),
+        ];
+        self.render_lines(&lines, beaufort_origin, target)?;
+
+        Ok(())
+    }
+}
@@ -7,26 +7,20 @@ use esp_idf_svc::{eventloop::EspSystemEventLoop, wifi::BlockingWifi, wifi::EspWi
 use esp_idf_svc::nvs::EspDefaultNvsPartition;
 use log::info;
 
+/// Starts the WiFi driver in station mode, `connect` joins the network
 pub fn wifi(
     ssid: &str,
-    pass: &str,
     modem: impl peripheral::Peripheral<P = esp_idf_hal::modem::Modem> + 'static,
     sysloop: EspSystemEventLoop,
     nvs: Option<EspDefaultNvsPartition>
 ) -> Result<Box<EspWifi<'static>>> {
-    let mut auth_method = AuthMethod::WPA2Personal;
     if ssid.is_empty() {
         bail!("Missing WiFi name")
     }
-    if pass.is_empty() {
-        auth_method = AuthMethod::None;
-        info!("Wifi password is empty");
-    }
-
-    let mut esp_wifi = EspWifi::new(modem, sysloop.clone(), nvs)?;
 
+    let mut esp_wifi = Box::new(EspWifi::new(modem, sysloop.clone(), nvs)?);
 
-    let mut wifi = BlockingWifi::wrap(&mut esp_wifi, sysloop)?;
+    let mut wifi = BlockingWifi::wrap(&mut *esp_wifi, sysloop)?;
 
     wifi.set_configuration(&Configuration::Client(ClientConfiguration::default()))?;
 
@@ -34,6 +28,29 @@ pub fn wifi(
 
     wifi.start()?;
 
+    Ok(esp_wifi)
+}
+
+/// Joins the network, can be called again after a failure
+pub fn connect(
+    esp_wifi: &mut EspWifi<'static>,
+    ssid: &str,
+    pass: &str,
+    sysloop: EspSystemEventLoop,
+) -> Result<()> {
+    let mut auth_method = AuthMethod::WPA2Personal;
+    if pass.is_empty() {
+        auth_method = AuthMethod::None;
+        info!("Wifi password is empty");
+    }
+
+    let mut wifi = BlockingWifi::wrap(esp_wifi, sysloop)?;
+
+    if wifi.is_connected()? {
+        // A previous attempt got connected but had no DHCP lease
+        wifi.disconnect()?;
+    }
+
     info!("Scanning...");
 
     let ap_infos = wifi.scan()?;
@@ -76,5 +93,5 @@ pub fn wifi(
 
     info!("Wifi DHCP info: {:?}", ip_info);
 
-    Ok(Box::new(esp_wifi))
-}
\ No newline at end of file
+    Ok(())
+}
@@ -0,0 +1,107 @@
+//! Conversions of the wind speed and direction reported by OpenWeather.
+
+/// Upper bound of each Beaufort force in m/s, force 12 is anything above
+const BEAUFORT_LIMITS: [f32; 12] = [0.5, 1.6, 3.4, 5.5, 8.0, 10.8, 13.9, 17.2, 20.8, 24.5, 28.5, 32.7];
+
+const BEAUFORT_DESCRIPTIONS: [&str; 13] = [
+    "Calm",
+    "Light air",
+    "Light breeze",
+    "Gentle breeze",
+    "Moderate breeze",
+    "Fresh breeze",
+    "Strong breeze",
+    "Near gale",
+    "Gale",
+    "Strong gale",
+    "Storm",
+    "Violent storm",
+    "Hurricane force",
+];
+
+const CARDINAL_POINTS: [&str; 16] = [
+    "N", "NNE", "NE", "ENE", "E", "ESE", "SE", "SSE",
+    "S", "SSW", "SW", "WSW", "W", "WNW", "NW", "NNW",
+];
+
+/// Wind force on the Beaufort scale, 0 to 12
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Beaufort(u8);
+
+impl Beaufort {
+    /// Force of a wind of `speed` m/s
+    pub fn from_speed(speed: f32) -> Self {
+        let force = BEAUFORT_LIMITS.iter()
+            .position(|limit| speed < *limit)
+            .unwrap_or(BEAUFORT_LIMITS.len());
+        Beaufort(force as u8)
+    }
+
+    pub fn force(&self) -> u8 {
+        self.0
+    }
+
+    pub fn description(&self) -> &'static str {
+        BEAUFORT_DESCRIPTIONS[self.0 as usize]
+    }
+}
+
+/// Closest of the 16 compass points to a direction in degrees, e.g. "NNE"
+pub fn cardinal(degrees: u16) -> &'static str {
+    let sector = 360.0 / CARDINAL_POINTS.len() as f32;
+    let index = ((degrees % 360) as f32 / sector).round() as usize % CARDINAL_POINTS.len();
+    CARDINAL_POINTS[index]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn beaufort_boundaries() {
+        let cases = [
+            (0.0, 0), (0.49, 0), (0.5, 1), (1.59, 1), (1.6, 2), (3.4, 3), (5.5, 4), (7.99, 4),
+            (8.0, 5), (10.8, 6), (13.9, 7), (17.2, 8), (20.8, 9), (24.5, 10), (28.49, 10),
+            (28.5, 11), (32.69, 11), (32.7, 12), (70.0, 12),
+        ];
+        for (speed, force) in cases {
+            assert_eq!(Beaufort::from_speed(speed).force(), force, "{} m/s", speed);
+        }
+    }
+
+    #[test]
+    fn beaufort_descriptions() {
+        assert_eq!(Beaufort::from_speed(0.0).description(), "Calm");
+        assert_eq!(Beaufort::from_speed(18.0).description(), "Gale");
+        assert_eq!(Beaufort::from_speed(40.0).description(), "Hurricane force");
+    }
+
+    #[test]
+    fn beaufort_never_decreases() {
+        let forces: Vec<u8> = (0..400).map(|tenths| Beaufort::from_speed(tenths as f32 / 10.0).force()).collect();
+        assert!(forces.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert_eq!(forces.last(), Some(&12));
+    }
+
+    #[test]
+    fn cardinal_points() {
+        let cases = [
+            (0, "N"), (11, "N"), (12, "NNE"), (22, "NNE"), (45, "NE"), (90, "E"), (135, "SE"),
+            (180, "S"), (202, "SSW"), (225, "SW"), (270, "W"), (315, "NW"), (337, "NNW"),
+        ];
+        for (degrees, point) in cases {
+            assert_eq!(cardinal(degrees), point, "{}°", degrees);
+        }
+    }
+
+    #[test]
+    fn cardinal_wraps_around_north() {
+        assert_eq!(cardinal(348), "NNW");
+        assert_eq!(cardinal(349), "N");
+        assert_eq!(cardinal(359), "N");
+        assert_eq!(cardinal(360), "N");
+        assert_eq!(cardinal(361), "N");
+        assert_eq!(cardinal(450), "E");
+        assert_eq!(cardinal(u16::MAX), cardinal(u16::MAX % 360));
+    }
+}
@@ -0,0 +1,471 @@
+//! Renders the icon sources listed in `assets/icons.toml` to the QOI files
+//! embedded in the firmware, run on the host:
+//!
+//! ```sh
+//! cd tools/assets
+//! cargo run --release --target x86_64-unknown-linux-gnu -- icons
+//! cargo run --release --target x86_64-unknown-linux-gnu -- icons --check
+//! ```
+//!
+//! `--check` renders everything in memory and fails if a checked-in file is
+//! missing, differs or has no source, so stale assets can be caught before
+//! they are flashed. Files matching a `prebuilt` prefix of the manifest are
+//! checked in without a source and left alone.
+//! The target must be given, the firmware's `.cargo/config.toml` selects the ESP32 otherwise.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use anyhow::{bail, Context, Result};
+use resvg::usvg::{self, TreeParsing};
+use serde::Deserialize;
+use tiny_skia::{FilterQuality, IntSize, Pixmap, PixmapPaint, PremultipliedColorU8, Transform};
+
+const ROOT: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../..");
+const MANIFEST: &str = "assets/icons.toml";
+const SOURCES: &str = "assets/icons";
+const OUTPUT: &str = "src/icons/qoi";
+
+#[derive(Deserialize)]
+struct Manifest {
+    sizes: Vec<u32>,
+    /// Name prefixes of icons checked in as QOI only
+    #[serde(default)]
+    prebuilt: Vec<String>,
+    #[serde(default)]
+    icon: Vec<IconSource>,
+}
+
+#[derive(Deserialize)]
+struct IconSource {
+    source: PathBuf,
+    /// Output name, the source file name by default
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    sizes: Option<Vec<u32>>,
+    #[serde(default = "default_rotations")]
+    rotations: Vec<f32>,
+    #[serde(default)]
+    rotation_steps: Vec<f32>,
+    #[serde(default)]
+    ink: Ink,
+    #[serde(default)]
+    dither: Dither,
+}
+
+fn default_rotations() -> Vec<f32> {
+    vec![0.0]
+}
+
+/// How the glyph is drawn in its source
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+enum Ink {
+    /// Dark on a light or transparent background, like most SVG icons
+    #[default]
+    Dark,
+    /// Light on a dark or transparent background, like the rendered set
+    Light,
+}
+
+impl Ink {
+    /// Ink level of a pixel composited over the paper, 255 being full ink.
+    /// Colors count by their luminance, the way they would print in gray.
+    fn level(self, pixel: PremultipliedColorU8) -> f32 {
+        let paper = match self {
+            Ink::Dark => 255.0,
+            Ink::Light => 0.0,
+        };
+        let background = paper * (1.0 - pixel.alpha() as f32 / 255.0);
+        let luma = 0.299 * (pixel.red() as f32 + background)
+            + 0.587 * (pixel.green() as f32 + background)
+            + 0.114 * (pixel.blue() as f32 + background);
+        match self {
+            Ink::Dark => 255.0 - luma,
+            Ink::Light => luma,
+        }
+    }
+}
+
+/// Pre-dithering for 1-bit panels, the runtime dithering then leaves pixels as they are
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+enum Dither {
+    #[default]
+    None,
+    Threshold,
+    Atkinson,
+}
+
+/// Output file relative to `OUTPUT` and its content
+struct Rendered {
+    path: PathBuf,
+    qoi: Vec<u8>,
+}
+
+fn main() -> Result<()> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let check = args.iter().any(|arg| arg == "--check");
+    match args.first().map(String::as_str) {
+        Some("icons") => icons(check),
+        _ => bail!("Usage: assets icons [--check]"),
+    }
+}
+
+fn icons(check: bool) -> Result<()> {
+    let root = Path::new(ROOT);
+    let manifest: Manifest = toml::from_str(&fs::read_to_string(root.join(MANIFEST))?)
+        .with_context(|| format!("Invalid {}", MANIFEST))?;
+
+    let mut rendered = Vec::new();
+    let mut sources = BTreeMap::new();
+    for icon in &manifest.icon {
+        let sizes = icon.sizes.as_ref().unwrap_or(&manifest.sizes);
+        for file in render_icon(&root.join(SOURCES), icon, sizes)? {
+            if let Some(other) = sources.insert(file.path.clone(), &icon.source) {
+                bail!("{} is rendered from both {} and {}", file.path.display(), other.display(), icon.source.display());
+            }
+            rendered.push(file);
+        }
+    }
+
+    let output = root.join(OUTPUT);
+    let unmanaged: Vec<PathBuf> = checked_in(&output)?.into_iter()
+        .filter(|path| !sources.contains_key(path) && !is_prebuilt(&manifest.prebuilt, path))
+        .collect();
+    for path in &unmanaged {
+        println!("no source: {}", path.display());
+    }
+
+    if !check {
+        for file in &rendered {
+            let path = output.join(&file.path);
+            fs::create_dir_all(path.parent().unwrap())?;
+            fs::write(&path, &file.qoi)?;
+        }
+        println!("Wrote {} icons", rendered.len());
+        return Ok(());
+    }
+
+    let mut stale = 0;
+    for file in &rendered {
+        match fs::read(output.join(&file.path)) {
+            Ok(existing) if existing == file.qoi => {}
+            Ok(_) => {
+                println!("outdated: {}", file.path.display());
+                stale += 1;
+            }
+            Err(_) => {
+                println!("missing: {}", file.path.display());
+                stale += 1;
+            }
+        }
+    }
+
+    println!("{} icons checked", rendered.len());
+
+    if stale > 0 {
+        bail!("{} icons out of date, run `cargo run --release -- icons`", stale);
+    }
+    if !unmanaged.is_empty() {
+        bail!("{} icons have no source, add them to {} or delete them", unmanaged.len(), MANIFEST);
+    }
+    Ok(())
+}
+
+fn render_icon(sources: &Path, icon: &IconSource, sizes: &[u32]) -> Result<Vec<Rendered>> {
+    let source = sources.join(&icon.source);
+    let data = fs::read(&source).with_context(|| format!("Can't read {}", source.display()))?;
+    let name = match &icon.name {
+        Some(name) => name.clone(),
+        None => icon.source.file_stem().and_then(|stem| stem.to_str()).context("Source without a name")?.to_string(),
+    };
+    let rotations = rotations(&icon.rotations, &icon.rotation_steps)
+        .with_context(|| format!("Invalid rotations of {}", name))?;
+
+    let mut rendered = Vec::new();
+    for &size in sizes {
+        for &angle in &rotations {
+            let pixmap = match source.extension().and_then(|ext| ext.to_str()) {
+                Some("svg") => render_svg(&data, size, angle),
+                Some("png") => render_png(&data, size, angle),
+                _ => bail!("{}: only SVG and PNG sources are supported", source.display()),
+            }
+            .with_context(|| format!("Can't render {}", source.display()))?;
+
+            let name = name.replace("{angle}", &format_angle(angle));
+            rendered.push(Rendered {
+                path: Path::new(&format!("{0}x{0}", size)).join(format!("{}.qoi", name)),
+                qoi: encode(&pixmap, icon.ink, icon.dither)?,
+            });
+        }
+    }
+    Ok(rendered)
+}
+
+/// `rotations` and every multiple of each step below 360, sorted
+fn rotations(rotations: &[f32], steps: &[f32]) -> Result<Vec<f32>> {
+    let mut all = rotations.to_vec();
+    for &step in steps {
+        if step <= 0.0 {
+            bail!("Rotation steps must be positive");
+        }
+        all.extend((0..).map(|i| i as f32 * step).take_while(|angle| *angle < 360.0));
+    }
+    all.sort_by(f32::total_cmp);
+    all.dedup();
+    Ok(all)
+}
+
+fn render_svg(data: &[u8], size: u32, angle: f32) -> Result<Pixmap> {
+    let tree = usvg::Tree::from_data(data, &usvg::Options::default())?;
+    let tree = resvg::Tree::from_usvg(&tree);
+    let source_size = tree.size.width().max(tree.size.height());
+
+    let mut pixmap = Pixmap::new(size, size).context("Empty icon size")?;
+    tree.render(transform(source_size, size, angle), &mut pixmap.as_mut());
+    Ok(pixmap)
+}
+
+fn render_png(data: &[u8], size: u32, angle: f32) -> Result<Pixmap> {
+    let mut source = Pixmap::decode_png(data)?;
+    if angle != 0.0 {
+        source = rotate(&source, angle)?;
+    }
+    let source_size = source.width().max(source.height());
+    if source.width() == size && source.height() == size {
+        return Ok(source);
+    }
+    if source_size > size {
+        return downscale(&source, size);
+    }
+
+    let mut pixmap = Pixmap::new(size, size).context("Empty icon size")?;
+    let paint = PixmapPaint { quality: FilterQuality::Bicubic, ..Default::default() };
+    pixmap.draw_pixmap(0, 0, source.as_ref(), &paint, transform(source_size as f32, size, 0.0), None);
+    Ok(pixmap)
+}
+
+/// Turns a raster source clockwise around its center, at its own resolution
+fn rotate(source: &Pixmap, angle: f32) -> Result<Pixmap> {
+    // Quarter turns of square sources move pixels as they are
+    if angle % 90.0 == 0.0 && source.width() == source.height() {
+        let size = source.width() as usize;
+        let mut rotated = source.clone();
+        for _ in 0..(angle / 90.0).rem_euclid(4.0) as usize {
+            let previous = rotated.clone();
+            for (index, pixel) in rotated.pixels_mut().iter_mut().enumerate() {
+                let (x, y) = (index % size, index / size);
+                *pixel = previous.pixels()[(size - 1 - x) * size + y];
+            }
+        }
+        return Ok(rotated);
+    }
+
+    let paint = PixmapPaint { quality: FilterQuality::Bicubic, ..Default::default() };
+    let center = (source.width() as f32 / 2.0, source.height() as f32 / 2.0);
+    let mut rotated = Pixmap::new(source.width(), source.height()).context("Empty source")?;
+    rotated.draw_pixmap(0, 0, source.as_ref(), &paint, Transform::from_rotate_at(angle, center.0, center.1), None);
+    Ok(rotated)
+}
+
+/// Averages the source pixels covered by each output pixel. Bicubic sampling
+/// only looks at 4x4 pixels and drops thin strokes when shrinking a lot.
+fn downscale(source: &Pixmap, size: u32) -> Result<Pixmap> {
+    let (width, height) = (source.width() as usize, source.height() as usize);
+    // Source pixels per output pixel
+    let scale = width.max(height) as f32 / size as f32;
+    let covered = |pixel: usize, start: f32, end: f32| (end.min(pixel as f32 + 1.0) - start.max(pixel as f32)).max(0.0);
+
+    let mut data = Vec::with_capacity((size * size * 4) as usize);
+    for out_y in 0..size {
+        let (top, bottom) = (out_y as f32 * scale, (out_y + 1) as f32 * scale);
+        for out_x in 0..size {
+            let (left, right) = (out_x as f32 * scale, (out_x + 1) as f32 * scale);
+            let mut sum = [0.0f32; 4];
+            for y in top as usize..(bottom.ceil() as usize).min(height) {
+                let weight_y = covered(y, top, bottom);
+                for x in left as usize..(right.ceil() as usize).min(width) {
+                    let weight = weight_y * covered(x, left, right);
+                    let pixel = &source.data()[(y * width + x) * 4..][..4];
+                    for (total, channel) in sum.iter_mut().zip(pixel) {
+                        *total += weight * *channel as f32;
+                    }
+                }
+            }
+            // Premultiplied channels stay below alpha when averaged and rounded alike
+            data.extend(sum.map(|total| (total / (scale * scale)).round().min(255.0) as u8));
+        }
+    }
+    let size = IntSize::from_wh(size, size).context("Empty icon size")?;
+    Pixmap::from_vec(data, size).context("Invalid downscaled icon")
+}
+
+/// Scales a square source to `size` and turns it clockwise around its center
+fn transform(source_size: f32, size: u32, angle: f32) -> Transform {
+    let scale = size as f32 / source_size;
+    let center = size as f32 / 2.0;
+    Transform::from_scale(scale, scale).post_rotate_at(angle, center, center)
+}
+
+/// Ink as a white glyph on black, in 3 channels like the rest of the set
+fn encode(pixmap: &Pixmap, ink: Ink, dither: Dither) -> Result<Vec<u8>> {
+    let mut levels: Vec<f32> = pixmap.pixels().iter().map(|pixel| snap(ink.level(*pixel))).collect();
+    let width = pixmap.width() as usize;
+    match dither {
+        Dither::None => {}
+        Dither::Threshold => levels.iter_mut().for_each(|level| *level = quantize(*level)),
+        Dither::Atkinson => atkinson(&mut levels, width),
+    }
+
+    let rgb: Vec<u8> = levels.iter()
+        .flat_map(|level| [level.round().clamp(0.0, 255.0) as u8; 3])
+        .collect();
+    Ok(qoi::encode_to_vec(&rgb, pixmap.width(), pixmap.height())?)
+}
+
+/// Resampling leaves faint ringing around strokes, invisible once dithered
+/// but breaking the runs QOI compresses well
+fn snap(level: f32) -> f32 {
+    match level {
+        level if level < 3.0 => 0.0,
+        level if level > 252.0 => 255.0,
+        level => level,
+    }
+}
+
+fn quantize(level: f32) -> f32 {
+    if level >= 128.0 { 255.0 } else { 0.0 }
+}
+
+/// Same diffusion as `Dithered` in the firmware, so pre-dithered icons look the same
+fn atkinson(levels: &mut [f32], width: usize) {
+    const SPREAD: [(isize, isize); 6] = [(1, 0), (2, 0), (-1, 1), (0, 1), (1, 1), (0, 2)];
+
+    let height = levels.len() / width;
+    for y in 0..height {
+        for x in 0..width {
+            let old = levels[y * width + x];
+            let new = quantize(old);
+            levels[y * width + x] = new;
+            let error = (old - new) / 8.0;
+            for (dx, dy) in SPREAD {
+                let (nx, ny) = (x as isize + dx, y as isize + dy);
+                if nx >= 0 && (nx as usize) < width && (ny as usize) < height {
+                    levels[ny as usize * width + nx as usize] += error;
+                }
+            }
+        }
+    }
+}
+
+/// `22.5` as `22_5`, the way rotated icons are named
+fn format_angle(angle: f32) -> String {
+    let formatted = format!("{}", (angle * 100.0).round() / 100.0);
+    formatted.replace('.', "_")
+}
+
+/// Whether `path` is one of the icons without a source
+fn is_prebuilt(prefixes: &[String], path: &Path) -> bool {
+    let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+    prefixes.iter().any(|prefix| name.starts_with(prefix.as_str()))
+}
+
+/// QOI files under `output`, relative to it
+fn checked_in(output: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for dir in fs::read_dir(output)? {
+        let dir = dir?.path();
+        if !dir.is_dir() {
+            continue;
+        }
+        for file in fs::read_dir(&dir)? {
+            let file = file?.path();
+            if file.extension().is_some_and(|ext| ext == "qoi") {
+                files.push(file.strip_prefix(output)?.to_path_buf());
+            }
+        }
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tiny_skia::ColorU8;
+
+    fn pixel(red: u8, green: u8, blue: u8, alpha: u8) -> PremultipliedColorU8 {
+        ColorU8::from_rgba(red, green, blue, alpha).premultiply()
+    }
+
+    fn pixel_black() -> PremultipliedColorU8 {
+        pixel(0, 0, 0, 255)
+    }
+
+    #[test]
+    fn ink_follows_luminance() {
+        // Opaque black and white on either kind of source
+        assert_eq!(Ink::Dark.level(pixel(0, 0, 0, 255)).round(), 255.0);
+        assert_eq!(Ink::Dark.level(pixel(255, 255, 255, 255)).round(), 0.0);
+        assert_eq!(Ink::Light.level(pixel(255, 255, 255, 255)).round(), 255.0);
+        assert_eq!(Ink::Light.level(pixel(0, 0, 0, 255)).round(), 0.0);
+        // Transparent pixels are paper, whatever their color
+        assert_eq!(Ink::Dark.level(pixel(0, 0, 0, 0)).round(), 0.0);
+        assert_eq!(Ink::Light.level(pixel(255, 255, 255, 0)).round(), 0.0);
+        // Half covered black is half ink
+        assert!((Ink::Dark.level(pixel(0, 0, 0, 128)) - 128.0).abs() <= 1.0);
+        // Yellow prints much lighter than blue
+        let yellow = Ink::Dark.level(pixel(255, 255, 0, 255));
+        let blue = Ink::Dark.level(pixel(0, 0, 255, 255));
+        assert!(yellow < 40.0 && blue > 200.0, "yellow {} blue {}", yellow, blue);
+    }
+
+    #[test]
+    fn downscale_averages_covered_pixels() {
+        // 4x4 checkerboard of opaque black and transparent pixels
+        let mut source = Pixmap::new(4, 4).unwrap();
+        for (index, pixel) in source.pixels_mut().iter_mut().enumerate() {
+            if (index % 4 + index / 4) % 2 == 0 {
+                *pixel = pixel_black();
+            }
+        }
+        let half = downscale(&source, 2).unwrap();
+        for pixel in half.pixels() {
+            assert_eq!(pixel.alpha(), 128);
+        }
+        // Non integer ratios still cover every source pixel once
+        let third = downscale(&source, 3).unwrap();
+        let total: f32 = third.pixels().iter().map(|pixel| pixel.alpha() as f32).sum();
+        assert!((total * 16.0 / 9.0 - 8.0 * 255.0).abs() < 16.0, "{}", total);
+    }
+
+    #[test]
+    fn quarter_turns_move_pixels_clockwise() {
+        let mut source = Pixmap::new(4, 4).unwrap();
+        // Top left corner
+        source.pixels_mut()[0] = pixel_black();
+        let rotated = rotate(&source, 90.0).unwrap();
+        // Ends up in the top right corner
+        assert_eq!(rotated.pixels()[3], pixel_black());
+        assert_eq!(rotated.pixels().iter().filter(|pixel| pixel.alpha() > 0).count(), 1);
+    }
+
+    #[test]
+    fn rotation_steps_are_merged() {
+        let all = rotations(&[0.0, 45.0], &[90.0, 120.0]).unwrap();
+        assert_eq!(all, [0.0, 45.0, 90.0, 120.0, 180.0, 240.0, 270.0]);
+        assert!(rotations(&[], &[0.0]).is_err());
+        assert_eq!(format_angle(11.25), "11_25");
+        assert_eq!(format_angle(90.0), "90");
+    }
+    #[test]
+    fn prebuilt_prefixes_match_file_names() {
+        let prefixes = vec!["wi-".to_string(), "battery_".to_string()];
+        assert!(is_prebuilt(&prefixes, Path::new("64x64/wi-day-sunny.qoi")));
+        assert!(is_prebuilt(&prefixes, Path::new("16x16/battery_alert_90deg.qoi")));
+        assert!(!is_prebuilt(&prefixes, Path::new("64x64/outline-sun.qoi")));
+        assert!(!is_prebuilt(&prefixes, Path::new("wi-/outline-sun.qoi")));
+    }
+}
@@ -0,0 +1,30 @@
+#!/usr/bin/env bash
+# Cargo runner, see .cargo/config.toml: checks the app image fits its OTA slot,
+# then flashes it. Both slots of partitions.csv have the size of `ota_0`.
+#
+# The image is built with the same espflash that flashes it, so its size is the
+# one written to flash, icons included.
+set -euo pipefail
+
+root="$(cd "$(dirname "$0")/.." && pwd)"
+partitions="$root/partitions.csv"
+elf="$1"
+shift
+
+slot=$(awk -F, '$1 ~ /^ota_0 *$/ { gsub(/ /, "", $5); print $5 }' "$partitions")
+if [ -z "$slot" ]; then
+    echo "No ota_0 partition in $partitions" >&2
+    exit 1
+fi
+
+image="$elf.bin"
+espflash save-image --chip esp32 "$elf" "$image" > /dev/null
+size=$(stat -c %s "$image")
+if [ "$size" -gt $((slot)) ]; then
+    echo "App image is $size bytes, the $slot ota_0 slot holds $((slot)) bytes." >&2
+    echo "Disable icon sizes (icons-<size> features) or grow the slots in partitions.csv." >&2
+    exit 1
+fi
+echo "App image: $size of $((slot)) bytes ($((size * 100 / slot))%)"
+
+exec espflash flash --monitor --partition-table "$partitions" "$elf" "$@"
@@ -0,0 +1,16 @@
+// Same icon registry as the firmware, see build.rs at the root
+#[path = "../../src/icons/codegen.rs"]
+mod codegen;
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let root = Path::new("../../src/icons/qoi");
+    println!("cargo:rerun-if-changed={}", root.display());
+
+    let code = codegen::icon_registry(root)?;
+    fs::write(Path::new(&env::var("OUT_DIR")?).join("icons.rs"), code)?;
+    Ok(())
+}
@@ -0,0 +1,74 @@
+//! Unit tests of the firmware modules that don't depend on the ESP32, run on the host:
+//!
+//! ```sh
+//! cd tools/host-tests
+//! cargo test --target x86_64-unknown-linux-gnu
+//! ```
+//!
+//! The modules are compiled as is and mounted at the same paths as in the
+//! firmware, so their `crate::` imports resolve. Tests live next to the code
+//! they test, in `#[cfg(test)]` modules.
+//! The target must be given, the firmware's `.cargo/config.toml` selects the ESP32 otherwise.
+
+#![allow(dead_code)]
+
+#[path = "../../../src/display"]
+pub mod display {
+    pub mod dither;
+    pub mod diff;
+}
+
+#[path = "../../../src/layout/mod.rs"]
+pub mod layout;
+
+#[path = "../../../src/pages/mod.rs"]
+pub mod pages;
+
+#[path = "../../../src/input"]
+pub mod input {
+    pub mod gesture;
+}
+
+#[path = "../../../src/astronomy"]
+pub mod astronomy {
+    pub mod moon;
+    pub mod sun;
+}
+
+#[path = "../../../src/icons"]
+pub mod icons {
+    pub mod registry;
+    pub mod glyphs;
+    pub mod theme;
+}
+
+#[path = "../../../src/text/mod.rs"]
+pub mod text;
+
+#[path = "../../../src/wind/mod.rs"]
+pub mod wind;
+
+#[path = "../../../src/owm"]
+pub mod owm {
+    pub mod de;
+    pub mod model;
+    pub mod nowcast;
+    pub mod geo {
+        pub mod place;
+    }
+}
+
+#[path = "../../../src/storage"]
+pub mod storage {
+    mod kv;
+
+    pub use kv::KeyValue;
+}
+
+#[path = "../../../src/locations/mod.rs"]
+pub mod locations;
+
+#[path = "../../../src/ota"]
+pub mod ota {
+    pub mod manifest;
+}
@@ -0,0 +1,102 @@
+//! Peak heap used to parse OneCall reports, run on the host:
+//!
+//! ```sh
+//! cd tools/model-bench
+//! cargo run --release --target x86_64-unknown-linux-gnu -- fixtures/*.json
+//! ```
+//!
+//! The target must be given, the firmware's `.cargo/config.toml` selects the ESP32 otherwise.
+
+// The model is compiled as is, it must not depend on anything target specific
+#[allow(dead_code)]
+#[path = "../../../src/owm"]
+mod owm {
+    pub mod de;
+    pub mod model;
+}
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::env;
+use std::fs;
+use std::io::{BufReader, Read};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use owm::model::WeatherData;
+
+/// Same read size as `http_client`
+const CHUNK_SIZE: usize = 1024;
+
+/// Counts the bytes in use and the most ever in use
+struct CountingAllocator;
+
+static IN_USE: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let in_use = IN_USE.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+            PEAK.fetch_max(in_use, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        IN_USE.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Peak and retained bytes allocated by `parse`, on top of what was in use before
+fn measure(parse: impl FnOnce() -> WeatherData) -> (usize, usize) {
+    let before = IN_USE.load(Ordering::Relaxed);
+    PEAK.store(before, Ordering::Relaxed);
+    let report = parse();
+    let retained = IN_USE.load(Ordering::Relaxed) - before;
+    let peak = PEAK.load(Ordering::Relaxed) - before;
+    drop(report);
+    (peak, retained)
+}
+
+/// Hands out the payload in small reads, like a network connection
+struct Chunked<'a>(&'a [u8]);
+
+impl Read for Chunked<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let size = buf.len().min(self.0.len()).min(CHUNK_SIZE);
+        buf[..size].copy_from_slice(&self.0[..size]);
+        self.0 = &self.0[size..];
+        Ok(size)
+    }
+}
+
+fn main() {
+    let paths: Vec<String> = env::args().skip(1).collect();
+    if paths.is_empty() {
+        eprintln!("Usage: model-bench <fixture.json>...");
+        std::process::exit(2);
+    }
+
+    println!("{:<32} {:>8} {:>14} {:>14} {:>10}", "fixture", "size", "buffered peak", "streamed peak", "retained");
+    for path in paths {
+        let payload = fs::read(&path).unwrap_or_else(|err| panic!("Reading {} failed: {}", path, err));
+
+        // Whole body in a String first, as the firmware used to
+        let (buffered, _) = measure(|| {
+            let mut text = String::new();
+            Chunked(&payload).read_to_string(&mut text).expect("Invalid UTF-8");
+            serde_json::from_str(&text).expect("Invalid report")
+        });
+        // Parsed while it is received, as `HttpClient::get_json` does
+        let (streamed, retained) = measure(|| {
+            serde_json::from_reader(BufReader::with_capacity(CHUNK_SIZE, Chunked(&payload))).expect("Invalid report")
+        });
+
+        let name = Path::new(&path).file_name().map_or(path.clone(), |name| name.to_string_lossy().into_owned());
+        println!("{:<32} {:>8} {:>14} {:>14} {:>10}", name, payload.len(), buffered, streamed, retained);
+    }
+}